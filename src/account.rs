@@ -0,0 +1,343 @@
+use ethcontract::prelude::*;
+use ethcontract::web3::signing::keccak256;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+#[derive(structopt::StructOpt)]
+#[structopt(about = "Manage accounts stored in encrypted keystores")]
+pub enum AccountCommand {
+    #[structopt(about = "Generate a new account")]
+    New {
+        #[structopt(help = "Name of the account, use it with `--from`")]
+        name: String,
+    },
+
+    #[structopt(about = "List stored accounts")]
+    List,
+
+    #[structopt(about = "Store an existing private key, it is asked for on the terminal")]
+    Import {
+        #[structopt(help = "Name of the account, use it with `--from`")]
+        name: String,
+    },
+
+    #[structopt(about = "Print address of a stored account")]
+    ExportAddress {
+        #[structopt(help = "Name of the account")]
+        name: String,
+    },
+}
+
+impl AccountCommand {
+    pub fn invoke(&self, dir: &Path) -> Result<()> {
+        match self {
+            AccountCommand::New { name } => {
+                let path = new_keystore_path(dir, name)?;
+                let password = ask_new_password()?;
+
+                let key = PrivateKey::from_raw(rand::random()).map_err(|err| {
+                    Error::Config(format!("generated invalid private key: {}", err))
+                })?;
+                crate::keystore::encrypt(&path, &key, &password)?;
+
+                println!("Address: {}", checksum(key.public_address()));
+                println!("Keystore: {}", path.display());
+            }
+
+            AccountCommand::List => {
+                let accounts = list_keystores(dir)?;
+                if accounts.is_empty() {
+                    println!("No accounts in {}", dir.display());
+                }
+
+                let width = accounts.iter().map(|(name, _)| name.len()).max();
+                for (name, path) in &accounts {
+                    let address = describe_keystore(path);
+                    println!(
+                        "{:<width$}  {}",
+                        name,
+                        address,
+                        width = width.unwrap_or_default()
+                    );
+                }
+            }
+
+            AccountCommand::Import { name } => {
+                let path = new_keystore_path(dir, name)?;
+
+                let key = rpassword::read_password_from_tty(Some("Private key: "))
+                    .map_err(|err| Error::Config(format!("can't read private key: {}", err)))?;
+                let key = PrivateKey::from_hex_str(key.trim())
+                    .map_err(|err| Error::Input(format!("invalid private key: {}", err)))?;
+                let password = ask_new_password()?;
+
+                crate::keystore::encrypt(&path, &key, &password)?;
+
+                println!("Address: {}", checksum(key.public_address()));
+                println!("Keystore: {}", path.display());
+            }
+
+            AccountCommand::ExportAddress { name } => {
+                let path = stored_keystore_path(dir, name)?;
+                match crate::keystore::read_address(&path)? {
+                    Some(address) => println!("{}", checksum(address)),
+                    None => {
+                        return Err(Error::Config(format!(
+                            "keystore {} doesn't say its address",
+                            path.display()
+                        )))
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Directory where named accounts are stored.
+///
+/// Defaults to `~/.fake-ico/keystore` unless given on the command line
+/// or in `ETH_KEYSTORE_DIR`.
+pub fn keystore_dir(dir: Option<&Path>) -> Result<PathBuf> {
+    if let Some(dir) = dir {
+        return Ok(dir.to_path_buf());
+    }
+
+    if let Some(dir) = std::env::var_os("ETH_KEYSTORE_DIR") {
+        return Ok(dir.into());
+    }
+
+    let home = std::env::var_os("HOME").ok_or_else(|| {
+        Error::Config("can't find home directory, set ETH_KEYSTORE_DIR".to_string())
+    })?;
+    Ok(Path::new(&home).join(".fake-ico").join("keystore"))
+}
+
+/// Path to the keystore of a named account.
+fn keystore_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(Error::Input(format!(
+            "invalid account name {:?}, use letters, digits, dashes and underscores",
+            name
+        )));
+    }
+
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+/// Path to the keystore of an existing account.
+pub fn stored_keystore_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let path = keystore_path(dir, name)?;
+    if !path.exists() {
+        return Err(Error::Config(format!(
+            "no account named {} in {}",
+            name,
+            dir.display()
+        )));
+    }
+
+    Ok(path)
+}
+
+/// Path to the keystore of an account that doesn't exist yet.
+///
+/// Creates the keystore directory if needed; on unix, only its owner
+/// can access it. The keystore itself refuses to overwrite an existing
+/// file, this check only fails early, before asking for a password.
+fn new_keystore_path(dir: &Path, name: &str) -> Result<PathBuf> {
+    let path = keystore_path(dir, name)?;
+    if path.exists() {
+        return Err(Error::Input(format!("account {} already exists", name)));
+    }
+
+    let mut builder = std::fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt as _;
+        builder.mode(0o700);
+    }
+
+    builder.create(dir).map_err(|err| {
+        Error::Config(format!("can't create directory {}: {}", dir.display(), err))
+    })?;
+
+    Ok(path)
+}
+
+/// Address of a stored account as `account list` shows it.
+///
+/// A broken keystore doesn't stop the listing, it's shown with the reason
+/// it can't be read instead.
+fn describe_keystore(path: &Path) -> String {
+    match crate::keystore::read_address(path) {
+        Ok(Some(address)) => checksum(address),
+        Ok(None) => "address unknown".to_string(),
+        Err(Error::Config(message)) => format!("unreadable, {}", message),
+        Err(err) => format!("unreadable, {}", err),
+    }
+}
+
+/// Names and keystores of all stored accounts, sorted by name.
+fn list_keystores(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(Error::Config(format!(
+                "can't read directory {}: {}",
+                dir.display(),
+                err
+            )))
+        }
+    };
+
+    let mut accounts = Vec::new();
+    for entry in entries {
+        let path = entry
+            .map_err(|err| {
+                Error::Config(format!("can't read directory {}: {}", dir.display(), err))
+            })?
+            .path();
+        let name = match path.file_name().and_then(|name| name.to_str()) {
+            Some(name) => name.strip_suffix(".json"),
+            None => None,
+        };
+
+        if let Some(name) = name {
+            accounts.push((name.to_string(), path.clone()));
+        }
+    }
+
+    accounts.sort();
+    Ok(accounts)
+}
+
+/// Ask for a password for a new keystore, twice.
+fn ask_new_password() -> Result<String> {
+    let read = |prompt| {
+        rpassword::read_password_from_tty(Some(prompt))
+            .map_err(|err| Error::Config(format!("can't read password: {}", err)))
+    };
+
+    let password = read("Password: ")?;
+    if password != read("Repeat password: ")? {
+        return Err(Error::Input("passwords don't match".to_string()));
+    }
+
+    Ok(password)
+}
+
+/// Format address with EIP-55 mixed-case checksum.
+pub fn checksum(address: Address) -> String {
+    let hex = format!("{:x}", address);
+    let hex = hex.trim_start_matches("0x");
+    let hash = keccak256(hex.as_bytes());
+
+    let checksummed: String = hex
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> if i % 2 == 0 { 4 } else { 0 }) & 0xf;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+
+    format!("0x{}", checksummed)
+}
+
+#[cfg(test)]
+mod test_account {
+    use super::*;
+
+    #[test]
+    fn eip55_checksum() {
+        // Test vectors from EIP-55.
+        for expected in &[
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let address: Address = expected[2..].parse().unwrap();
+            assert_eq!(checksum(address), *expected);
+        }
+    }
+
+    #[test]
+    fn account_names() {
+        let dir = Path::new("/keys");
+        assert_eq!(
+            keystore_path(dir, "deployer-1").unwrap(),
+            Path::new("/keys/deployer-1.json")
+        );
+        assert!(matches!(keystore_path(dir, ""), Err(Error::Input(_))));
+        assert!(matches!(
+            keystore_path(dir, "../deployer"),
+            Err(Error::Input(_))
+        ));
+    }
+
+    #[test]
+    fn list_stored_accounts() {
+        let dir = std::env::temp_dir().join(format!("fake-ico-test-list-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("bob.json"), "{}").unwrap();
+        std::fs::write(dir.join("alice.json"), "{}").unwrap();
+        std::fs::write(dir.join("notes.txt"), "").unwrap();
+        std::fs::write(
+            dir.join("carol.json"),
+            r#"{"address": "f39fd6e51aad88f6f4ce6ab8827279cfffb92266"}"#,
+        )
+        .unwrap();
+        std::fs::write(dir.join("broken.json"), "").unwrap();
+
+        let accounts: Vec<_> = list_keystores(&dir)
+            .unwrap()
+            .into_iter()
+            .map(|(name, path)| (name, describe_keystore(&path)))
+            .collect();
+        let names: Vec<_> = accounts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["alice", "bob", "broken", "carol"]);
+
+        assert_eq!(accounts[0].1, "address unknown");
+        assert!(accounts[2].1.starts_with("unreadable, can't read keystore"));
+        assert_eq!(accounts[3].1, "0xf39Fd6e51aad88F6F4ce6aB8827279cffFb92266");
+
+        assert!(list_keystores(&dir.join("missing")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn new_account_path() {
+        let dir = std::env::temp_dir()
+            .join(format!("fake-ico-test-new-{}", std::process::id()))
+            .join("keystore");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let path = new_keystore_path(&dir, "alice").unwrap();
+        assert_eq!(path, dir.join("alice.json"));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = std::fs::metadata(&dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o700);
+        }
+
+        std::fs::write(&path, "{}").unwrap();
+        assert!(matches!(
+            new_keystore_path(&dir, "alice"),
+            Err(Error::Input(_))
+        ));
+    }
+}
@@ -0,0 +1,179 @@
+use ethcontract::prelude::*;
+use std::fmt::{Display, Formatter};
+
+use crate::cli::Token;
+
+/// Everything `ico audit` needs to know, fetched at a single block.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AuditFacts {
+    /// `ICO.target`.
+    pub target: U256,
+
+    /// `ICO.leftEth`.
+    pub left_eth: U256,
+
+    /// `ICO.leftScm`.
+    pub left_scm: U256,
+
+    /// Sum of `ethUsed` over all `Fund` events.
+    pub raised: U256,
+
+    /// Sum of `ICO.balanceScm` over all buyers.
+    pub claimable: U256,
+
+    /// `SCM.balanceOf(ICO)`.
+    pub ico_scm_balance: U256,
+
+    /// `SCM.totalSupply`.
+    pub total_supply: U256,
+
+    /// `ICO.toScm(target)`.
+    pub target_scm: U256,
+}
+
+/// Result of checking a single invariant.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub details: String,
+}
+
+impl Display for Check {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let status = if self.ok { "ok" } else { "FAIL" };
+        write!(f, "[{}] {}: {}", status, self.name, self.details)
+    }
+}
+
+/// Check ICO invariants.
+///
+/// - every ETH is either raised or left to collect;
+/// - ICO holds exactly the SCM that are not claimed yet, sold or unsold;
+/// - SCM supply is what ICO is selling.
+pub fn audit(facts: &AuditFacts) -> Vec<Check> {
+    let eth = Token::eth();
+    let scm = Token::scm();
+
+    let collected = facts.raised.checked_add(facts.left_eth);
+    let held = facts.claimable.checked_add(facts.left_scm);
+
+    vec![
+        Check {
+            name: "raised + left ETH == target",
+            ok: collected == Some(facts.target),
+            details: format!(
+                "{} + {} {} {}",
+                eth.amount(facts.raised),
+                eth.amount(facts.left_eth),
+                if collected == Some(facts.target) {
+                    "=="
+                } else {
+                    "!="
+                },
+                eth.amount(facts.target)
+            ),
+        },
+        Check {
+            name: "ICO SCM balance == claimable + unsold SCM",
+            ok: held == Some(facts.ico_scm_balance),
+            details: format!(
+                "{} {} {} + {}",
+                scm.amount(facts.ico_scm_balance),
+                if held == Some(facts.ico_scm_balance) {
+                    "=="
+                } else {
+                    "!="
+                },
+                scm.amount(facts.claimable),
+                scm.amount(facts.left_scm)
+            ),
+        },
+        Check {
+            name: "SCM total supply == toScm(target)",
+            ok: facts.total_supply == facts.target_scm,
+            details: format!(
+                "{} {} {}",
+                scm.amount(facts.total_supply),
+                if facts.total_supply == facts.target_scm {
+                    "=="
+                } else {
+                    "!="
+                },
+                scm.amount(facts.target_scm)
+            ),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod test_audit {
+    use super::*;
+
+    fn healthy() -> AuditFacts {
+        AuditFacts {
+            target: 10.into(),
+            left_eth: 3.into(),
+            left_scm: 30.into(),
+            raised: 7.into(),
+            claimable: 50.into(),
+            ico_scm_balance: 80.into(),
+            total_supply: 100.into(),
+            target_scm: 100.into(),
+        }
+    }
+
+    #[test]
+    fn healthy_ico() {
+        let checks = audit(&healthy());
+        assert_eq!(checks.len(), 3);
+        assert!(checks.iter().all(|check| check.ok));
+    }
+
+    #[test]
+    fn raised_mismatch() {
+        let facts = AuditFacts {
+            raised: 6.into(),
+            ..healthy()
+        };
+        let checks = audit(&facts);
+        assert!(!checks[0].ok);
+        assert!(checks[1].ok && checks[2].ok);
+        assert_eq!(
+            checks[0].to_string(),
+            "[FAIL] raised + left ETH == target: \
+             0.000000000000000006eth + 0.000000000000000003eth != 0.000000000000000010eth"
+        );
+    }
+
+    #[test]
+    fn scm_balance_mismatch() {
+        let facts = AuditFacts {
+            ico_scm_balance: 90.into(),
+            ..healthy()
+        };
+        let checks = audit(&facts);
+        assert!(!checks[1].ok);
+        assert!(checks[0].ok && checks[2].ok);
+    }
+
+    #[test]
+    fn total_supply_mismatch() {
+        let facts = AuditFacts {
+            total_supply: 200.into(),
+            ..healthy()
+        };
+        let checks = audit(&facts);
+        assert!(!checks[2].ok);
+        assert!(checks[0].ok && checks[1].ok);
+    }
+
+    #[test]
+    fn overflow_is_a_mismatch() {
+        let facts = AuditFacts {
+            raised: U256::MAX,
+            ..healthy()
+        };
+        assert!(!audit(&facts)[0].ok);
+    }
+}
@@ -2,69 +2,510 @@ use ethcontract::prelude::*;
 use std::str::FromStr;
 use std::fmt::{Display, Formatter};
 
-/// Trait for CLI arguments that represent an amount of some currency.
-pub trait Currency: FromStr {
-    /// Get the underlying int type.
-    fn as_inner(&self) -> U256;
+/// Error that can occur when parsing an amount of some currency.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AmountParseError {
+    /// Amount ends with a suffix that doesn't name any known unit.
+    UnknownSuffix {
+        suffix: String,
+        expected: Vec<String>,
+    },
+
+    /// There is no number before the suffix, i.e. `eth` or `.gwei`.
+    EmptyNumber,
+
+    /// Number contains a character that is not a valid digit.
+    /// Position is counted from 1.
+    InvalidDigit { position: usize, digit: char },
+
+    /// Amount doesn't fit into 256 bits.
+    Overflow,
+
+    /// Number has more fractional digits than the unit allows,
+    /// i.e. `1.5wei`.
+    TooManyDecimals { decimals: usize, max: usize },
 }
 
-/// For CLI arguments that take amount of ether.
+impl Display for AmountParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AmountParseError::UnknownSuffix { suffix, expected } => f.write_fmt(format_args!(
+                "unknown suffix `{}`, accepted suffixes are: {}",
+                suffix,
+                expected.join(", ")
+            )),
+            AmountParseError::EmptyNumber => f.write_str("amount is empty"),
+            AmountParseError::InvalidDigit { position, digit } => f.write_fmt(format_args!(
+                "invalid digit `{}` at position {}",
+                digit, position
+            )),
+            AmountParseError::Overflow => f.write_str("amount is too large"),
+            AmountParseError::TooManyDecimals { decimals, max } => f.write_fmt(format_args!(
+                "too many decimal places: got {}, unit allows at most {}",
+                decimals, max
+            )),
+        }
+    }
+}
+
+impl std::error::Error for AmountParseError {}
+
+/// Parse an amount with an optional unit suffix.
 ///
-/// Supports parsing ether values in human-readable form,
-/// i.e. `1eth` or `100gwei` or others.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-pub struct Eth(U256);
+/// Units are given as pairs of a suffix and the power of ten it stands for.
+/// Amounts without a suffix are assumed to be in the smallest unit.
+fn parse_amount(s: &str, units: &[(String, usize)]) -> Result<U256, AmountParseError> {
+    let lower = s.to_ascii_lowercase();
+
+    let unit = units
+        .iter()
+        .filter(|(suffix, _)| lower.ends_with(suffix))
+        .max_by_key(|(suffix, _)| suffix.len());
+
+    let (number, exponent) = match unit {
+        Some((suffix, exponent)) => (&s[..s.len() - suffix.len()], *exponent),
+        None if lower.starts_with("0x") => (s, 0),
+        None => {
+            let number = s.trim_end_matches(|c: char| c.is_ascii_alphabetic());
+            if number.len() < s.len() {
+                return Err(AmountParseError::UnknownSuffix {
+                    suffix: s[number.len()..].to_string(),
+                    expected: units.iter().map(|(suffix, _)| suffix.clone()).collect(),
+                });
+            }
+            (s, 0)
+        }
+    };
+
+    parse_scaled(number, exponent)
+}
 
-impl Eth {
-    pub fn new(inner: U256) -> Self {
-        Self(inner)
+/// Parse an integer or a decimal fraction and multiply it by `10^exponent`.
+///
+/// Hex numbers are supported, but they can't have a fractional part.
+fn parse_scaled(s: &str, exponent: usize) -> Result<U256, AmountParseError> {
+    if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        if hex.is_empty() {
+            return Err(AmountParseError::EmptyNumber);
+        }
+
+        return parse_digits(hex, 16, 2)?
+            .checked_mul(pow10(exponent)?)
+            .ok_or(AmountParseError::Overflow);
     }
+
+    let (int, frac) = s.split_once('.').unwrap_or((s, ""));
+
+    if int.is_empty() && frac.is_empty() {
+        return Err(AmountParseError::EmptyNumber);
+    }
+
+    let int_value = parse_digits(int, 10, 0)?;
+    let frac_value = parse_digits(frac, 10, int.len() + 1)?;
+
+    // Trailing zeroes don't change the value, so `1.0wei` is fine.
+    let decimals = frac.trim_end_matches('0').len();
+    if decimals > exponent {
+        return Err(AmountParseError::TooManyDecimals {
+            decimals,
+            max: exponent,
+        });
+    }
+
+    let frac_value = if frac.len() > exponent {
+        frac_value / pow10(frac.len() - exponent)?
+    } else {
+        frac_value * pow10(exponent - frac.len())?
+    };
+
+    int_value
+        .checked_mul(pow10(exponent)?)
+        .and_then(|value| value.checked_add(frac_value))
+        .ok_or(AmountParseError::Overflow)
+}
+
+/// Parse a string of digits. Offset is used to report position of an invalid digit.
+fn parse_digits(s: &str, radix: u32, offset: usize) -> Result<U256, AmountParseError> {
+    s.char_indices().try_fold(U256::zero(), |value, (i, c)| {
+        let digit = c.to_digit(radix).ok_or(AmountParseError::InvalidDigit {
+            position: offset + i + 1,
+            digit: c,
+        })?;
+
+        value
+            .checked_mul(radix.into())
+            .and_then(|value| value.checked_add(digit.into()))
+            .ok_or(AmountParseError::Overflow)
+    })
 }
 
-impl Currency for Eth {
-    fn as_inner(&self) -> U256 {
-        self.0
+fn pow10(exponent: usize) -> Result<U256, AmountParseError> {
+    U256::from(10)
+        .checked_pow(exponent.into())
+        .ok_or(AmountParseError::Overflow)
+}
+
+/// Describes a token: how its amounts are parsed and displayed.
+///
+/// Descriptors are either static, like [`Token::eth`], or built from token's
+/// on-chain `symbol()` and `decimals()`, see [`Token::from_metadata`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Token {
+    symbol: String,
+    decimals: usize,
+    units: Vec<(String, usize)>,
+}
+
+const ETH_UNITS: &[(&str, usize)] = &[
+    ("ether", 18),
+    ("eth", 18),
+    ("pwei", 15),
+    ("twei", 12),
+    ("gwei", 9),
+    ("mwei", 6),
+    ("kwei", 3),
+    ("wei", 0),
+];
+
+const SCM_UNITS: &[(&str, usize)] = &[
+    ("scam", 18),
+    ("scm", 18),
+    ("msc", 15),
+    ("usc", 12),
+    ("nsc", 9),
+    ("psc", 6),
+    ("fsc", 3),
+    ("asc", 0),
+];
+
+impl Token {
+    /// Create a descriptor for a token that only knows its own symbol
+    /// and its smallest unit.
+    ///
+    /// Symbol is only accepted as a suffix if it consists of letters;
+    /// otherwise amounts can only be given in the smallest units.
+    pub fn new(symbol: &str, decimals: u8) -> Self {
+        let symbol = symbol.to_ascii_lowercase();
+        let is_suffix = !symbol.is_empty() && symbol.chars().all(|c| c.is_ascii_alphabetic());
+        Token {
+            units: if is_suffix {
+                vec![(symbol.clone(), decimals as usize)]
+            } else {
+                Vec::new()
+            },
+            symbol,
+            decimals: decimals as usize,
+        }
+    }
+
+    /// Ether, with units from `wei` to `ether`.
+    pub fn eth() -> Self {
+        Self::with_units("eth", 18, ETH_UNITS)
+    }
+
+    /// SCM, with units from `asc` (atta-scam) to `scam`.
+    pub fn scm() -> Self {
+        Self::with_units("scm", 18, SCM_UNITS)
+    }
+
+    /// Create a descriptor for a token with the given on-chain metadata.
+    ///
+    /// If we have a static descriptor for this token, it is used instead,
+    /// so that SCM amounts can still be given in `nsc` and others.
+    pub fn from_metadata(symbol: &str, decimals: u8) -> Self {
+        let known = Token::scm();
+        if known.symbol.eq_ignore_ascii_case(symbol) && known.decimals == decimals as usize {
+            known
+        } else {
+            Token::new(symbol, decimals)
+        }
+    }
+
+    fn with_units(symbol: &str, decimals: usize, units: &[(&str, usize)]) -> Self {
+        Token {
+            symbol: symbol.to_string(),
+            decimals,
+            units: units
+                .iter()
+                .map(|(suffix, exponent)| (suffix.to_string(), *exponent))
+                .collect(),
+        }
+    }
+
+    /// Convert amount given on the command line to the number of
+    /// the smallest units of this token.
+    pub fn parse(&self, amount: &Amount) -> Result<U256, AmountParseError> {
+        parse_amount(&amount.0, &self.units)
+    }
+
+    /// Wrap a raw value to display it as an amount of this token.
+    pub fn amount(&self, value: U256) -> TokenAmount<'_> {
+        TokenAmount { value, token: self }
     }
 }
 
-impl FromStr for Eth {
-    type Err = uint::FromStrRadixErr;
+/// For CLI arguments that take amount of some token.
+///
+/// Supports parsing amounts in human-readable form, i.e. `1eth`, `1.5scm`
+/// or `100gwei`. Which suffixes are accepted depends on the token,
+/// so the actual value is only known after calling [`Token::parse`].
+///
+/// Amounts of ETH and SCM are known upfront, see [`EthAmount`] and [`ScmAmount`].
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Amount(String);
+
+impl FromStr for Amount {
+    type Err = AmountParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_ascii_lowercase();
-
-        let (s, modifier) = if let Some(s) = s.strip_suffix("ether") {
-            (s, U256::exp10(18))
-        } else if let Some(s) = s.strip_suffix("eth") {
-            (s, U256::exp10(18))
-        } else if let Some(s) = s.strip_suffix("pwei") {
-            (s, U256::exp10(15))
-        } else if let Some(s) = s.strip_suffix("twei") {
-            (s, U256::exp10(12))
-        } else if let Some(s) = s.strip_suffix("gwei") {
-            (s, U256::exp10(9))
-        } else if let Some(s) = s.strip_suffix("mwei") {
-            (s, U256::exp10(6))
-        } else if let Some(s) = s.strip_suffix("kwei") {
-            (s, U256::exp10(3))
-        } else if let Some(s) = s.strip_suffix("wei") {
-            (s, U256::exp10(0))
+        if s.is_empty() {
+            Err(AmountParseError::EmptyNumber)
         } else {
-            (s.as_str(), U256::exp10(0))
-        };
+            Ok(Amount(s.to_string()))
+        }
+    }
+}
 
-        if s.starts_with("0x") {
-            Ok(Eth(U256::from_str_radix(s, 16)? * modifier))
-        } else {
-            Ok(Eth(U256::from_str_radix(s, 10)? * modifier))
+/// Amount argument of a command that is shared between tokens.
+///
+/// Commands for tokens with on-chain metadata take an [`Amount`] and parse
+/// it once the token is known; commands for a token with a static
+/// descriptor take an already parsed amount, so that typos are reported
+/// as usage errors.
+pub trait AmountArg: FromStr<Err = AmountParseError> {
+    /// Number of the smallest units of the given token.
+    fn value(&self, token: &Token) -> Result<U256, AmountParseError>;
+}
+
+impl AmountArg for Amount {
+    fn value(&self, token: &Token) -> Result<U256, AmountParseError> {
+        token.parse(self)
+    }
+}
+
+/// Amount of ether, parsed with [`Token::eth`] units.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct EthAmount(pub U256);
+
+impl FromStr for EthAmount {
+    type Err = AmountParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_amount(s, &Token::eth().units).map(EthAmount)
+    }
+}
+
+impl AmountArg for EthAmount {
+    fn value(&self, _: &Token) -> Result<U256, AmountParseError> {
+        Ok(self.0)
+    }
+}
+
+/// Amount of SCM, parsed with [`Token::scm`] units.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ScmAmount(pub U256);
+
+impl FromStr for ScmAmount {
+    type Err = AmountParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_amount(s, &Token::scm().units).map(ScmAmount)
+    }
+}
+
+/// Raw value paired with a token descriptor, used to display token amounts.
+#[derive(Copy, Clone, Debug)]
+pub struct TokenAmount<'a> {
+    value: U256,
+    token: &'a Token,
+}
+
+impl Display for TokenAmount<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let decimals = self.token.decimals;
+        match U256::from(10).checked_pow(decimals.into()) {
+            Some(one) if decimals > 0 => {
+                let (int, frac) = self.value.div_mod(one);
+                f.write_fmt(format_args!(
+                    "{}.{:0>width$}{}",
+                    int,
+                    frac.to_string(),
+                    self.token.symbol,
+                    width = decimals
+                ))
+            }
+            _ => {
+                let value = self.value.to_string();
+                if decimals > value.len() {
+                    f.write_fmt(format_args!(
+                        "0.{:0>width$}{}",
+                        value,
+                        self.token.symbol,
+                        width = decimals
+                    ))
+                } else {
+                    f.write_fmt(format_args!("{}{}", value, self.token.symbol))
+                }
+            }
+        }
+    }
+}
+
+/// Error that can occur when parsing a duration.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DurationParseError {
+    /// Duration string is empty.
+    Empty,
+
+    /// Duration contains something other than numbers and `d`, `h`, `m`, `s`.
+    UnknownUnit { unit: char },
+
+    /// There is no number before a unit, i.e. `m` or `1h m`.
+    MissingNumber { unit: char },
+
+    /// Duration doesn't fit into 64 bits.
+    Overflow,
+}
+
+impl Display for DurationParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DurationParseError::Empty => f.write_str("duration is empty"),
+            DurationParseError::UnknownUnit { unit } => f.write_fmt(format_args!(
+                "unknown unit `{}`, accepted units are: d, h, m, s",
+                unit
+            )),
+            DurationParseError::MissingNumber { unit } => {
+                f.write_fmt(format_args!("no number before unit `{}`", unit))
+            }
+            DurationParseError::Overflow => f.write_str("duration is too large"),
+        }
+    }
+}
+
+impl std::error::Error for DurationParseError {}
+
+/// Number of seconds, displayed as `1d 2h 3m 4s` with zero parts omitted.
+///
+/// Parses from the same format; a number without a unit is in seconds.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Duration(pub u64);
+
+impl FromStr for Duration {
+    type Err = DurationParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(DurationParseError::Empty);
         }
+
+        let mut total = 0u64;
+        let mut number: Option<u64> = None;
+
+        for c in s.chars() {
+            if let Some(digit) = c.to_digit(10) {
+                number = number
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|number| number.checked_add(digit.into()));
+                if number.is_none() {
+                    return Err(DurationParseError::Overflow);
+                }
+                continue;
+            }
+
+            if c.is_whitespace() {
+                continue;
+            }
+
+            let seconds = match c {
+                'd' => 86400,
+                'h' => 3600,
+                'm' => 60,
+                's' => 1,
+                unit => return Err(DurationParseError::UnknownUnit { unit }),
+            };
+
+            let value = number
+                .take()
+                .ok_or(DurationParseError::MissingNumber { unit: c })?;
+
+            total = value
+                .checked_mul(seconds)
+                .and_then(|value| total.checked_add(value))
+                .ok_or(DurationParseError::Overflow)?;
+        }
+
+        total = total
+            .checked_add(number.unwrap_or(0))
+            .ok_or(DurationParseError::Overflow)?;
+
+        Ok(Duration(total))
+    }
+}
+
+impl From<Duration> for std::time::Duration {
+    fn from(duration: Duration) -> Self {
+        std::time::Duration::from_secs(duration.0)
     }
 }
 
-impl Display for Eth {
+impl Display for Duration {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let (eth, wei) = self.0.div_mod(U256::exp10(18));
-        f.write_fmt(format_args!("{}.{:0>18}eth", eth, wei.as_u64()))
+        let parts = [
+            (self.0 / 86400, "d"),
+            (self.0 / 3600 % 24, "h"),
+            (self.0 / 60 % 60, "m"),
+            (self.0 % 60, "s"),
+        ];
+
+        let mut parts = parts.iter().filter(|(value, _)| *value > 0).peekable();
+        if parts.peek().is_none() {
+            return f.write_str("0s");
+        }
+
+        let mut separator = "";
+        for (value, unit) in parts {
+            f.write_fmt(format_args!("{}{}{}", separator, value, unit))?;
+            separator = " ";
+        }
+
+        Ok(())
+    }
+}
+
+/// How to print tabular data.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum OutputFormat {
+    /// Human-readable table with token amounts in their usual units.
+    Table,
+
+    /// Comma-separated values, token amounts in base units.
+    Csv,
+
+    /// JSON array of objects, token amounts in base units.
+    Json,
+}
+
+impl OutputFormat {
+    /// Names accepted by [`OutputFormat::from_str`].
+    pub const VARIANTS: &'static [&'static str] = &["table", "csv", "json"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "table" => Ok(OutputFormat::Table),
+            "csv" => Ok(OutputFormat::Csv),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "unknown output format `{}`, accepted formats are: {}",
+                s,
+                Self::VARIANTS.join(", ")
+            )),
+        }
     }
 }
 
@@ -72,125 +513,287 @@ impl Display for Eth {
 mod test_eth {
     use super::*;
 
+    fn eth(s: &str) -> Result<U256, AmountParseError> {
+        Token::eth().parse(&s.parse()?)
+    }
+
     #[test]
     fn eth_from_str() -> Result<(), Box<dyn std::error::Error>> {
-        assert_eq!(Eth::from_str("0")?.0, U256::from(0));
-        assert_eq!(Eth::from_str("1")?.0, U256::from_dec_str("1")?);
-        assert_eq!(Eth::from_str("15")?.0, U256::from_dec_str("15")?);
-        assert_eq!(Eth::from_str("0x1")?.0, U256::from_dec_str("1")?);
-        assert_eq!(Eth::from_str("0x15")?.0, U256::from_dec_str("21")?);
-
-        assert_eq!(Eth::from_str("5wei")?.0, U256::from_dec_str("5")?);
-        assert_eq!(Eth::from_str("5kwei")?.0, U256::from_dec_str("5000")?);
-        assert_eq!(Eth::from_str("5mwei")?.0, U256::from_dec_str("5000000")?);
-        assert_eq!(Eth::from_str("5gwei")?.0, U256::from_dec_str("5000000000")?);
-        assert_eq!(Eth::from_str("5twei")?.0, U256::from_dec_str("5000000000000")?);
-        assert_eq!(Eth::from_str("5pwei")?.0, U256::from_dec_str("5000000000000000")?);
-        assert_eq!(Eth::from_str("5eth")?.0, U256::from_dec_str("5000000000000000000")?);
-        assert_eq!(Eth::from_str("5ether")?.0, U256::from_dec_str("5000000000000000000")?);
+        assert_eq!(eth("0")?, U256::from(0));
+        assert_eq!(eth("1")?, U256::from_dec_str("1")?);
+        assert_eq!(eth("15")?, U256::from_dec_str("15")?);
+        assert_eq!(eth("0x1")?, U256::from_dec_str("1")?);
+        assert_eq!(eth("0x15")?, U256::from_dec_str("21")?);
+
+        assert_eq!(eth("5wei")?, U256::from_dec_str("5")?);
+        assert_eq!(eth("5kwei")?, U256::from_dec_str("5000")?);
+        assert_eq!(eth("5mwei")?, U256::from_dec_str("5000000")?);
+        assert_eq!(eth("5gwei")?, U256::from_dec_str("5000000000")?);
+        assert_eq!(eth("5twei")?, U256::from_dec_str("5000000000000")?);
+        assert_eq!(eth("5pwei")?, U256::from_dec_str("5000000000000000")?);
+        assert_eq!(eth("5eth")?, U256::from_dec_str("5000000000000000000")?);
+        assert_eq!(eth("5ether")?, U256::from_dec_str("5000000000000000000")?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn eth_from_str_fractional() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(eth("0.25eth")?, U256::from_dec_str("250000000000000000")?);
+        assert_eq!(eth("1.5eth")?, U256::from_dec_str("1500000000000000000")?);
+        assert_eq!(eth(".001gwei")?, U256::from_dec_str("1000000")?);
+        assert_eq!(eth("2.gwei")?, U256::from_dec_str("2000000000")?);
+        assert_eq!(eth("1.000kwei")?, U256::from_dec_str("1000")?);
+
+        assert!(matches!(
+            eth("1.5wei"),
+            Err(AmountParseError::TooManyDecimals { decimals: 1, max: 0 })
+        ));
+        assert!(matches!(
+            eth("1.0001kwei"),
+            Err(AmountParseError::TooManyDecimals { decimals: 4, max: 3 })
+        ));
+        assert!(eth("0x1.5eth").is_err());
+        assert!(eth(".eth").is_err());
+        assert!(eth("1.2.3eth").is_err());
 
         Ok(())
     }
 
+    #[test]
+    fn eth_from_str_errors() {
+        assert_eq!(eth(""), Err(AmountParseError::EmptyNumber));
+        assert_eq!(eth("eth"), Err(AmountParseError::EmptyNumber));
+        assert_eq!(eth("0x"), Err(AmountParseError::EmptyNumber));
+        assert_eq!(
+            eth("1e70eth"),
+            Err(AmountParseError::InvalidDigit {
+                position: 2,
+                digit: 'e'
+            })
+        );
+        assert_eq!(
+            eth("0x1g"),
+            Err(AmountParseError::InvalidDigit {
+                position: 4,
+                digit: 'g'
+            })
+        );
+        assert_eq!(
+            eth("1.5.0eth"),
+            Err(AmountParseError::InvalidDigit {
+                position: 4,
+                digit: '.'
+            })
+        );
+        assert_eq!(
+            eth(&format!("1{}eth", "0".repeat(60))),
+            Err(AmountParseError::Overflow)
+        );
+        assert_eq!(
+            eth(&format!("0x1{}", "0".repeat(64))),
+            Err(AmountParseError::Overflow)
+        );
+        assert!(matches!(
+            eth("5btc"),
+            Err(AmountParseError::UnknownSuffix { suffix, .. }) if suffix == "btc"
+        ));
+        assert_eq!(eth("1.000wei"), Ok(U256::from(1)));
+    }
+
+    #[test]
+    fn eth_from_str_error_message() {
+        assert_eq!(
+            eth("5btc").unwrap_err().to_string(),
+            "unknown suffix `btc`, accepted suffixes are: ether, eth, pwei, twei, gwei, mwei, kwei, wei"
+        );
+    }
+
     #[test]
     fn eth_to_str() -> Result<(), Box<dyn std::error::Error>> {
-        assert_eq!(Eth::from_str("10eth")?.to_string(), "10.000000000000000000eth");
-        assert_eq!(Eth::from_str("1500pwei")?.to_string(), "1.500000000000000000eth");
+        assert_eq!(Token::eth().amount(eth("10eth")?).to_string(), "10.000000000000000000eth");
+        assert_eq!(Token::eth().amount(eth("1500pwei")?).to_string(), "1.500000000000000000eth");
+        assert_eq!(Token::eth().amount(eth("1.5eth")?).to_string(), "1.500000000000000000eth");
 
         Ok(())
     }
 }
 
-/// For CLI arguments that take amount of SCM tokens.
-///
-/// Supports parsing ether values in human-readable form,
-/// i.e. `1scm` or `100asc` or others.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
-pub struct Scm(U256);
+#[cfg(test)]
+mod test_scm {
+    use super::*;
 
-impl Scm {
-    pub fn new(inner: U256) -> Self {
-        Self(inner)
+    fn scm(s: &str) -> Result<U256, AmountParseError> {
+        Token::scm().parse(&s.parse()?)
     }
-}
 
-impl Currency for Scm {
-    fn as_inner(&self) -> U256 {
-        self.0
+    #[test]
+    fn eth_from_str() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(scm("0")?, U256::from(0));
+        assert_eq!(scm("1")?, U256::from_dec_str("1")?);
+        assert_eq!(scm("15")?, U256::from_dec_str("15")?);
+        assert_eq!(scm("0x1")?, U256::from_dec_str("1")?);
+        assert_eq!(scm("0x15")?, U256::from_dec_str("21")?);
+
+        assert_eq!(scm("5asc")?, U256::from_dec_str("5")?);
+        assert_eq!(scm("5fsc")?, U256::from_dec_str("5000")?);
+        assert_eq!(scm("5psc")?, U256::from_dec_str("5000000")?);
+        assert_eq!(scm("5nsc")?, U256::from_dec_str("5000000000")?);
+        assert_eq!(scm("5usc")?, U256::from_dec_str("5000000000000")?);
+        assert_eq!(scm("5msc")?, U256::from_dec_str("5000000000000000")?);
+        assert_eq!(scm("5scm")?, U256::from_dec_str("5000000000000000000")?);
+        assert_eq!(scm("5scam")?, U256::from_dec_str("5000000000000000000")?);
+
+        Ok(())
     }
-}
 
-impl FromStr for Scm {
-    type Err = uint::FromStrRadixErr;
+    #[test]
+    fn scm_from_str_fractional() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(scm("1.5scm")?, U256::from_dec_str("1500000000000000000")?);
+        assert_eq!(scm("0.5fsc")?, U256::from_dec_str("500")?);
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let s = s.to_ascii_lowercase();
-
-        let (s, modifier) = if let Some(s) = s.strip_suffix("scam") {
-            (s, U256::exp10(18))
-        } else if let Some(s) = s.strip_suffix("scm") {
-            (s, U256::exp10(18))
-        } else if let Some(s) = s.strip_suffix("msc") {
-            (s, U256::exp10(15))
-        } else if let Some(s) = s.strip_suffix("usc") {
-            (s, U256::exp10(12))
-        } else if let Some(s) = s.strip_suffix("nsc") {
-            (s, U256::exp10(9))
-        } else if let Some(s) = s.strip_suffix("psc") {
-            (s, U256::exp10(6))
-        } else if let Some(s) = s.strip_suffix("fsc") {
-            (s, U256::exp10(3))
-        } else if let Some(s) = s.strip_suffix("asc") {
-            (s, U256::exp10(0))
-        } else {
-            (s.as_str(), U256::exp10(0))
-        };
+        assert!(matches!(
+            scm("0.5asc"),
+            Err(AmountParseError::TooManyDecimals { decimals: 1, max: 0 })
+        ));
 
-        if s.starts_with("0x") {
-            Ok(Scm(U256::from_str_radix(s, 16)? * modifier))
-        } else {
-            Ok(Scm(U256::from_str_radix(s, 10)? * modifier))
-        }
+        Ok(())
     }
-}
 
-impl Display for Scm {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let (eth, wei) = self.0.div_mod(U256::exp10(18));
-        f.write_fmt(format_args!("{}.{:0>18}scm", eth, wei.as_u64()))
+
+    #[test]
+    fn scm_to_str() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(Token::scm().amount(scm("10scm")?).to_string(), "10.000000000000000000scm");
+        assert_eq!(Token::scm().amount(scm("1500msc")?).to_string(), "1.500000000000000000scm");
+
+        Ok(())
     }
 }
 
 #[cfg(test)]
-mod test_scm {
+mod test_token {
     use super::*;
 
+    fn parse(token: &Token, s: &str) -> Result<U256, AmountParseError> {
+        token.parse(&s.parse()?)
+    }
+
     #[test]
-    fn eth_from_str() -> Result<(), Box<dyn std::error::Error>> {
-        assert_eq!(Scm::from_str("0")?.0, U256::from(0));
-        assert_eq!(Scm::from_str("1")?.0, U256::from_dec_str("1")?);
-        assert_eq!(Scm::from_str("15")?.0, U256::from_dec_str("15")?);
-        assert_eq!(Scm::from_str("0x1")?.0, U256::from_dec_str("1")?);
-        assert_eq!(Scm::from_str("0x15")?.0, U256::from_dec_str("21")?);
-
-        assert_eq!(Scm::from_str("5asc")?.0, U256::from_dec_str("5")?);
-        assert_eq!(Scm::from_str("5fsc")?.0, U256::from_dec_str("5000")?);
-        assert_eq!(Scm::from_str("5psc")?.0, U256::from_dec_str("5000000")?);
-        assert_eq!(Scm::from_str("5nsc")?.0, U256::from_dec_str("5000000000")?);
-        assert_eq!(Scm::from_str("5usc")?.0, U256::from_dec_str("5000000000000")?);
-        assert_eq!(Scm::from_str("5msc")?.0, U256::from_dec_str("5000000000000000")?);
-        assert_eq!(Scm::from_str("5scm")?.0, U256::from_dec_str("5000000000000000000")?);
-        assert_eq!(Scm::from_str("5scam")?.0, U256::from_dec_str("5000000000000000000")?);
+    fn token_from_str() -> Result<(), Box<dyn std::error::Error>> {
+        let usdc = Token::new("USDC", 6);
+
+        assert_eq!(parse(&usdc, "15")?, U256::from(15));
+        assert_eq!(parse(&usdc, "1.5usdc")?, U256::from(1500000));
+        assert_eq!(parse(&usdc, "1.5USDC")?, U256::from(1500000));
+        assert_eq!(
+            parse(&usdc, "0.0000001usdc"),
+            Err(AmountParseError::TooManyDecimals {
+                decimals: 7,
+                max: 6
+            })
+        );
+        assert_eq!(
+            parse(&usdc, "1eth"),
+            Err(AmountParseError::UnknownSuffix {
+                suffix: "eth".to_string(),
+                expected: vec!["usdc".to_string()]
+            })
+        );
 
         Ok(())
     }
 
+    #[test]
+    fn token_to_str() {
+        let usdc = Token::new("USDC", 6);
+        assert_eq!(usdc.amount(U256::from(1500000)).to_string(), "1.500000usdc");
+        assert_eq!(usdc.amount(U256::from(15)).to_string(), "0.000015usdc");
+
+        let whole = Token::new("WHL", 0);
+        assert_eq!(whole.amount(U256::from(15)).to_string(), "15whl");
+
+        let tiny = Token::new("TNY", 80);
+        assert_eq!(
+            tiny.amount(U256::from(15)).to_string(),
+            format!("0.{}15tny", "0".repeat(78))
+        );
+    }
 
     #[test]
-    fn scm_to_str() -> Result<(), Box<dyn std::error::Error>> {
-        assert_eq!(Scm::from_str("10scm")?.to_string(), "10.000000000000000000scm");
-        assert_eq!(Scm::from_str("1500msc")?.to_string(), "1.500000000000000000scm");
+    fn token_from_metadata() {
+        assert_eq!(Token::from_metadata("SCM", 18), Token::scm());
+        assert_eq!(Token::from_metadata("SCM", 6), Token::new("SCM", 6));
+        assert_eq!(Token::from_metadata("WETH", 18), Token::new("WETH", 18));
+    }
+
+    #[test]
+    fn token_without_symbol() -> Result<(), Box<dyn std::error::Error>> {
+        let nameless = Token::new("", 6);
+        assert_eq!(parse(&nameless, "15")?, U256::from(15));
+        assert!(parse(&nameless, "1.5").is_err());
+
+        let odd = Token::new("T0K", 2);
+        assert_eq!(parse(&odd, "15")?, U256::from(15));
+        assert!(parse(&odd, "15t0k").is_err());
 
         Ok(())
     }
+
+    #[test]
+    fn static_amounts_from_str() {
+        let usdc = Token::new("USDC", 6);
+
+        assert_eq!("1.5gwei".parse(), Ok(EthAmount(1500000000.into())));
+        assert_eq!("1.5fsc".parse(), Ok(ScmAmount(1500.into())));
+        assert!("1.5wie".parse::<EthAmount>().is_err());
+        assert!("1eth".parse::<ScmAmount>().is_err());
+        assert_eq!(EthAmount(15.into()).value(&usdc), Ok(15.into()));
+    }
+}
+
+#[cfg(test)]
+mod test_duration {
+    use super::*;
+
+    #[test]
+    fn duration_to_str() {
+        assert_eq!(Duration(0).to_string(), "0s");
+        assert_eq!(Duration(12).to_string(), "12s");
+        assert_eq!(Duration(72).to_string(), "1m 12s");
+        assert_eq!(Duration(120).to_string(), "2m");
+        assert_eq!(Duration(3601).to_string(), "1h 1s");
+        assert_eq!(Duration(93784).to_string(), "1d 2h 3m 4s");
+    }
+
+    #[test]
+    fn duration_from_str() {
+        assert_eq!("0".parse(), Ok(Duration(0)));
+        assert_eq!("90".parse(), Ok(Duration(90)));
+        assert_eq!("90s".parse(), Ok(Duration(90)));
+        assert_eq!("1m 12s".parse(), Ok(Duration(72)));
+        assert_eq!("1h30m".parse(), Ok(Duration(5400)));
+        assert_eq!("1d 2h 3m 4s".parse(), Ok(Duration(93784)));
+        assert_eq!("1m 5".parse(), Ok(Duration(65)));
+    }
+
+    #[test]
+    fn duration_from_str_err() {
+        assert_eq!("".parse::<Duration>(), Err(DurationParseError::Empty));
+        assert_eq!(
+            "5w".parse::<Duration>(),
+            Err(DurationParseError::UnknownUnit { unit: 'w' })
+        );
+        assert_eq!(
+            "1h m".parse::<Duration>(),
+            Err(DurationParseError::MissingNumber { unit: 'm' })
+        );
+        assert_eq!(
+            "99999999999999999999".parse::<Duration>(),
+            Err(DurationParseError::Overflow)
+        );
+        assert_eq!(
+            "999999999999999999d".parse::<Duration>(),
+            Err(DurationParseError::Overflow)
+        );
+    }
 }
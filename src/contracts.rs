@@ -1,68 +1,158 @@
+use ethcontract::batch::CallBatch;
+use ethcontract::common::DeploymentInformation;
 use ethcontract::prelude::*;
 
+use crate::cli::Token;
+use crate::error::{Error, Result};
+use crate::signer::SigningTransport;
+
 ethcontract::contract!(pub "deployments/ICO.json");
 ethcontract::contract!(pub "deployments/SCM.json");
 ethcontract::contract!(pub "deployments/WETH9.json");
 
-pub async fn get_weth_address(web3: &Web3<Http>) -> Address {
+/// Describes where we've got a contract address from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AddressSource {
+    /// Address was given in the environment variable with this name.
+    Env(&'static str),
+
+    /// Address was found in the contract artifact for the network with this id.
+    Artifact(String),
+
+    /// Address was given as a command line argument.
+    CommandLine,
+}
+
+impl std::fmt::Display for AddressSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AddressSource::Env(var) => write!(f, "environment variable {}", var),
+            AddressSource::Artifact(net_id) => write!(f, "artifact for network {}", net_id),
+            AddressSource::CommandLine => write!(f, "command line"),
+        }
+    }
+}
+
+pub async fn get_weth_address(web3: &Web3<SigningTransport>) -> Result<(Address, AddressSource)> {
     if let Ok(address) = std::env::var("WETH_ADDRESS") {
-        return address.parse().expect("invalid WETH_ADDRESS");
+        let address = address
+            .parse()
+            .map_err(|_| Error::Config("invalid WETH_ADDRESS".to_string()))?;
+        return Ok((address, AddressSource::Env("WETH_ADDRESS")));
     }
 
-    let net_id = web3
-        .net()
-        .version()
-        .await
-        .expect("unable to fetch network id");
+    let net_id = web3.net().version().await?;
 
-    WETH9::artifact()
+    let address = WETH9::artifact()
         .networks
         .get(&net_id)
-        .expect(concat!(
-            "there is no known instance of WETH on this network; ",
-            "you should specify WETH address manually with WETH_ADDRESS environment variable"
-        ))
-        .address
+        .ok_or_else(|| {
+            Error::Config(
+                concat!(
+                    "there is no known instance of WETH on this network; ",
+                    "you should specify WETH address manually with WETH_ADDRESS environment variable"
+                )
+                .to_string(),
+            )
+        })?
+        .address;
+
+    Ok((address, AddressSource::Artifact(net_id)))
 }
 
-pub async fn get_scm_address(web3: &Web3<Http>) -> Address {
+pub async fn get_scm_address(web3: &Web3<SigningTransport>) -> Result<(Address, AddressSource)> {
     if let Ok(address) = std::env::var("SCM_ADDRESS") {
-        return address.parse().expect("invalid SCM_ADDRESS");
+        let address = address
+            .parse()
+            .map_err(|_| Error::Config("invalid SCM_ADDRESS".to_string()))?;
+        return Ok((address, AddressSource::Env("SCM_ADDRESS")));
     }
 
-    let net_id = web3
-        .net()
-        .version()
-        .await
-        .expect("unable to fetch network id");
+    let net_id = web3.net().version().await?;
 
-    SCM::artifact()
+    let address = SCM::artifact()
         .networks
         .get(&net_id)
-        .expect(concat!(
-            "there is no known instance of SCM on this network; ",
-            "you should specify SCM address manually with SCM_ADDRESS environment variable"
-        ))
-        .address
+        .ok_or_else(|| {
+            Error::Config(
+                concat!(
+                    "there is no known instance of SCM on this network; ",
+                    "you should specify SCM address manually with SCM_ADDRESS environment variable"
+                )
+                .to_string(),
+            )
+        })?
+        .address;
+
+    Ok((address, AddressSource::Artifact(net_id)))
 }
 
-pub async fn get_ico_address(web3: &Web3<Http>) -> Address {
+pub async fn get_ico_address(web3: &Web3<SigningTransport>) -> Result<(Address, AddressSource)> {
     if let Ok(address) = std::env::var("ICO_ADDRESS") {
-        return address.parse().expect("invalid ICO_ADDRESS");
+        let address = address
+            .parse()
+            .map_err(|_| Error::Config("invalid ICO_ADDRESS".to_string()))?;
+        return Ok((address, AddressSource::Env("ICO_ADDRESS")));
     }
 
-    let net_id = web3
-        .net()
-        .version()
-        .await
-        .expect("unable to fetch network id");
+    let net_id = web3.net().version().await?;
 
-    ICO::artifact()
+    let address = ICO::artifact()
         .networks
         .get(&net_id)
-        .expect(concat!(
-            "there is no known instance of ICO on this network; ",
-            "you should specify ICO address manually with ICO_ADDRESS environment variable"
-        ))
-        .address
+        .ok_or_else(|| {
+            Error::Config(
+                concat!(
+                    "there is no known instance of ICO on this network; ",
+                    "you should specify ICO address manually with ICO_ADDRESS environment variable"
+                )
+                .to_string(),
+            )
+        })?
+        .address;
+
+    Ok((address, AddressSource::Artifact(net_id)))
+}
+
+/// Find the block where the ICO at the given address was deployed,
+/// so that event queries don't have to scan the whole chain.
+///
+/// Falls back to the earliest block if the address doesn't come
+/// from the artifact.
+pub async fn get_ico_deployment_block(
+    web3: &Web3<SigningTransport>,
+    address: Address,
+) -> Result<BlockNumber> {
+    let net_id = web3.net().version().await?;
+
+    let deployment_information = ICO::artifact()
+        .networks
+        .get(&net_id)
+        .filter(|network| network.address == address)
+        .and_then(|network| network.deployment_information);
+
+    match deployment_information {
+        Some(DeploymentInformation::BlockNumber(block)) => Ok(BlockNumber::Number(block.into())),
+        Some(DeploymentInformation::TransactionHash(hash)) => {
+            let block = web3
+                .eth()
+                .transaction_receipt(hash)
+                .await?
+                .and_then(|receipt| receipt.block_number);
+            Ok(block.map_or(BlockNumber::Earliest, BlockNumber::Number))
+        }
+        None => Ok(BlockNumber::Earliest),
+    }
+}
+
+/// Fetch symbol and decimals of an ERC20 token and build a descriptor for it.
+pub async fn get_token(web3: &Web3<SigningTransport>, contract: &SCM) -> Result<Token> {
+    let mut batch = CallBatch::new(web3.transport());
+
+    let symbol = contract.symbol().batch_call(&mut batch);
+    let decimals = contract.decimals().batch_call(&mut batch);
+
+    batch.execute_all(100).await;
+
+    Ok(Token::from_metadata(&symbol.await?, decimals.await?))
 }
@@ -0,0 +1,279 @@
+use ethcontract::json::{json, Value};
+use ethcontract::prelude::*;
+use std::collections::HashMap;
+
+use crate::account::checksum;
+use crate::cli::Token;
+use crate::contracts::ico::event_data::Fund;
+
+/// Everything a single buyer has contributed to the ICO.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Contributor {
+    pub buyer: Address,
+
+    /// Sum of `ethUsed` over all `Fund` events of this buyer.
+    pub eth: U256,
+
+    /// Sum of `scmPurchased` over all `Fund` events of this buyer.
+    pub scm: U256,
+
+    /// What `ICO.balanceEth` says about this buyer.
+    pub balance_eth: U256,
+}
+
+/// How a buyer's `Fund` events compare to their ICO balance.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ContributorStatus {
+    /// ICO balance matches the events, tokens are not claimed yet.
+    Unclaimed,
+
+    /// ICO balance is zero, so tokens are already claimed.
+    Claimed,
+
+    /// ICO balance doesn't match the events.
+    Mismatch,
+}
+
+impl ContributorStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ContributorStatus::Unclaimed => "unclaimed",
+            ContributorStatus::Claimed => "claimed",
+            ContributorStatus::Mismatch => "mismatch",
+        }
+    }
+}
+
+impl Contributor {
+    pub fn status(&self) -> ContributorStatus {
+        if self.balance_eth == self.eth {
+            ContributorStatus::Unclaimed
+        } else if self.balance_eth.is_zero() {
+            ContributorStatus::Claimed
+        } else {
+            ContributorStatus::Mismatch
+        }
+    }
+}
+
+/// Total `Fund` events per buyer, biggest contributors first.
+///
+/// ICO balances are left at zero, they should be filled in separately.
+pub fn tally(funds: &[Fund]) -> Vec<Contributor> {
+    let mut totals: HashMap<Address, (U256, U256)> = HashMap::new();
+    for fund in funds {
+        let (eth, scm) = totals.entry(fund.buyer).or_default();
+        *eth = eth.saturating_add(fund.eth_used);
+        *scm = scm.saturating_add(fund.scm_purchased);
+    }
+
+    let mut contributors: Vec<_> = totals
+        .into_iter()
+        .map(|(buyer, (eth, scm))| Contributor {
+            buyer,
+            eth,
+            scm,
+            balance_eth: U256::zero(),
+        })
+        .collect();
+
+    contributors.sort_by(|a, b| b.eth.cmp(&a.eth).then(a.buyer.cmp(&b.buyer)));
+    contributors
+}
+
+/// Share of the target in basis points, i.e. `3500` for 35%.
+fn share_bps(eth: U256, target: U256) -> u64 {
+    if target.is_zero() {
+        return 0;
+    }
+
+    (eth.saturating_mul(10000.into()) / target)
+        .min(10000.into())
+        .as_u64()
+}
+
+/// Share of the target formatted as `35.00%`.
+fn share(eth: U256, target: U256) -> String {
+    let bps = share_bps(eth, target);
+    format!("{}.{:02}%", bps / 100, bps % 100)
+}
+
+/// Render contributors as a human-readable table.
+pub fn table(contributors: &[Contributor], target: U256) -> String {
+    let eth = Token::eth();
+    let scm = Token::scm();
+
+    let rows: Vec<[String; 5]> = contributors
+        .iter()
+        .map(|contributor| {
+            let status = match contributor.status() {
+                ContributorStatus::Mismatch => format!(
+                    "mismatch, ICO balance is {}",
+                    eth.amount(contributor.balance_eth)
+                ),
+                status => status.as_str().to_string(),
+            };
+
+            [
+                checksum(contributor.buyer),
+                eth.amount(contributor.eth).to_string(),
+                scm.amount(contributor.scm).to_string(),
+                share(contributor.eth, target),
+                status,
+            ]
+        })
+        .collect();
+
+    let header = ["Buyer", "ETH", "SCM", "Share", "Status"];
+    let mut widths = header.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut table = String::new();
+    let mut push_row = |cells: &[&str]| {
+        let line = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ");
+        table.push_str(line.trim_end());
+        table.push('\n');
+    };
+
+    push_row(&header);
+    for row in &rows {
+        push_row(&row.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    table
+}
+
+/// Render contributors as CSV, amounts are in base units.
+pub fn csv(contributors: &[Contributor], target: U256) -> String {
+    let mut csv = String::from("buyer,eth,scm,share,balance_eth,status\n");
+    for contributor in contributors {
+        csv.push_str(&format!(
+            "{:?},{},{},{},{},{}\n",
+            contributor.buyer,
+            contributor.eth,
+            contributor.scm,
+            share(contributor.eth, target).trim_end_matches('%'),
+            contributor.balance_eth,
+            contributor.status().as_str()
+        ));
+    }
+    csv
+}
+
+/// Render contributors as JSON, amounts are in base units.
+pub fn json(contributors: &[Contributor], target: U256) -> Value {
+    contributors
+        .iter()
+        .map(|contributor| {
+            json!({
+                "buyer": contributor.buyer,
+                "eth": contributor.eth.to_string(),
+                "scm": contributor.scm.to_string(),
+                "share": share_bps(contributor.eth, target) as f64 / 10000.0,
+                "balance_eth": contributor.balance_eth.to_string(),
+                "status": contributor.status().as_str(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_contributors {
+    use super::*;
+
+    fn fund(buyer: u8, eth_used: u64) -> Fund {
+        Fund {
+            buyer: Address::repeat_byte(buyer),
+            eth_used: eth_used.into(),
+            scm_purchased: (eth_used * 10).into(),
+        }
+    }
+
+    fn contributor(buyer: u8, eth: u64, balance_eth: u64) -> Contributor {
+        Contributor {
+            buyer: Address::repeat_byte(buyer),
+            eth: eth.into(),
+            scm: (eth * 10).into(),
+            balance_eth: balance_eth.into(),
+        }
+    }
+
+    #[test]
+    fn tally_sorts_by_eth() {
+        let funds = [fund(1, 2), fund(2, 5), fund(1, 4), fund(3, 1)];
+        assert_eq!(
+            tally(&funds),
+            vec![
+                contributor(1, 6, 0),
+                contributor(2, 5, 0),
+                contributor(3, 1, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn status() {
+        assert_eq!(contributor(1, 6, 6).status(), ContributorStatus::Unclaimed);
+        assert_eq!(contributor(1, 6, 0).status(), ContributorStatus::Claimed);
+        assert_eq!(contributor(1, 6, 4).status(), ContributorStatus::Mismatch);
+    }
+
+    #[test]
+    fn share_of_target() {
+        assert_eq!(share(35.into(), 100.into()), "35.00%");
+        assert_eq!(share(1.into(), 3.into()), "33.33%");
+        assert_eq!(share(0.into(), 0.into()), "0.00%");
+    }
+
+    #[test]
+    fn render_csv() {
+        let contributors = [contributor(1, 6, 6), contributor(2, 4, 0)];
+        assert_eq!(
+            csv(&contributors, 10.into()),
+            format!(
+                "buyer,eth,scm,share,balance_eth,status\n\
+                 {:?},6,60,60.00,6,unclaimed\n\
+                 {:?},4,40,40.00,0,claimed\n",
+                Address::repeat_byte(1),
+                Address::repeat_byte(2)
+            )
+        );
+    }
+
+    #[test]
+    fn render_json() {
+        let contributors = [contributor(1, 6, 4)];
+        assert_eq!(
+            json(&contributors, 10.into()),
+            json!([{
+                "buyer": "0x0101010101010101010101010101010101010101",
+                "eth": "6",
+                "scm": "60",
+                "share": 0.6,
+                "balance_eth": "4",
+                "status": "mismatch",
+            }])
+        );
+    }
+
+    #[test]
+    fn render_table() {
+        let contributors = [contributor(1, 6, 4)];
+        let table = table(&contributors, 10.into());
+        let lines: Vec<_> = table.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("Buyer"));
+        assert!(lines[1].starts_with(&checksum(Address::repeat_byte(1))));
+        assert!(lines[1].ends_with("60.00%  mismatch, ICO balance is 0.000000000000000004eth"));
+    }
+}
@@ -1,10 +1,20 @@
 use ethcontract::prelude::*;
 
-use crate::cli::{Currency, Eth, Scm};
-
+use crate::cli::{Amount, AmountArg, EthAmount, Token};
+use crate::contracts::AddressSource;
+use crate::error::{Error, Result};
+use crate::signer::SigningTransport;
+use ethcontract::batch::CallBatch;
+
+/// Commands that work with any ERC20 token.
+///
+/// All tokens are accessed through the SCM bindings, as they implement
+/// the whole ERC20 interface.
 #[derive(structopt::StructOpt)]
-#[structopt(about = "Manage SCM tokens")]
-pub enum ScmCommand {
+pub enum Erc20Command<A: AmountArg = Amount> {
+    #[structopt(about = "Get token name, symbol, decimals and total supply")]
+    Info,
+
     #[structopt(about = "Get balance of the given wallet")]
     Balance {
         #[structopt(help = "Account we're fetching balance for (uses your account by default)")]
@@ -16,7 +26,7 @@ pub enum ScmCommand {
         #[structopt(help = "Where are we transferring funds to")]
         recipient: Address,
         #[structopt(help = "Amount of funds we are transferring")]
-        funds: Scm,
+        funds: A,
         #[structopt(
             long,
             help = "Where are we transferring funds from (uses your account by default)"
@@ -39,20 +49,42 @@ pub enum ScmCommand {
         #[structopt(
             help = "Amount of funds they are allowed to withdraw (overrides previous allowance)"
         )]
-        value: Scm,
+        value: A,
     },
 }
 
-impl ScmCommand {
-    pub async fn invoke(&self, account: Account, web3: &Web3<Http>) {
+impl<A: AmountArg> Erc20Command<A> {
+    pub async fn invoke(
+        &self,
+        account: Account,
+        web3: &Web3<SigningTransport>,
+        contract: &crate::contracts::SCM,
+        source: &AddressSource,
+        token: &Token,
+    ) -> Result<()> {
         let account_address = account.address();
 
-        let contract_address = crate::contracts::get_scm_address(web3).await;
-        let contract = crate::contracts::SCM::at(web3, contract_address);
-
         match self {
+            Self::Info => {
+                let mut batch = CallBatch::new(web3.transport());
+
+                let name = contract.name().batch_call(&mut batch);
+                let symbol = contract.symbol().batch_call(&mut batch);
+                let decimals = contract.decimals().batch_call(&mut batch);
+                let total_supply = contract.total_supply().batch_call(&mut batch);
+
+                batch.execute_all(100).await;
+
+                println!("Name: {}", name.await?);
+                println!("Symbol: {}", symbol.await?);
+                println!("Decimals: {}", decimals.await?);
+                println!("Total supply: {}", token.amount(total_supply.await?));
+                println!("Address: {:?}", contract.address());
+                println!("Address source: {}", source);
+            }
+
             Self::Balance { address } => {
-                Self::print_balance(address.unwrap_or(account_address), &contract).await;
+                print_balance(address.unwrap_or(account_address), contract, token).await?;
             }
 
             Self::Transfer {
@@ -60,195 +92,249 @@ impl ScmCommand {
                 funds,
                 owner,
             } => {
-                contract
-                    .transfer_from(
-                        owner.unwrap_or(account_address),
-                        *recipient,
-                        funds.as_inner(),
-                    )
-                    .from(account)
-                    .send()
-                    .await
-                    .expect("transfer failed");
+                let funds = funds.value(token)?;
+                let owner = owner.unwrap_or(account_address);
+
+                let mut batch = CallBatch::new(web3.transport());
+
+                let balance = contract.balance_of(owner).batch_call(&mut batch);
+                let allowance = contract
+                    .allowance(owner, account_address)
+                    .batch_call(&mut batch);
+
+                batch.execute_all(100).await;
+
+                let balance = balance.await?;
+                let allowance = if owner == account_address {
+                    None
+                } else {
+                    Some(allowance.await?)
+                };
+
+                check_transfer(token, balance, allowance, funds).map_err(Error::Input)?;
+
+                if owner == account_address {
+                    contract
+                        .transfer(*recipient, funds)
+                        .from(account)
+                        .send()
+                        .await?;
+                } else {
+                    contract
+                        .transfer_from(owner, *recipient, funds)
+                        .from(account)
+                        .send()
+                        .await?;
+                }
 
                 println!("Done");
-                Self::print_balance(account_address, &contract).await;
+                print_balance(owner, contract, token).await?;
             }
 
             Self::Allowance { owner, spender } => {
-                let allowance = contract
-                    .allowance(*owner, *spender)
-                    .call()
-                    .await
-                    .expect("allowance fetch failed");
+                let allowance = contract.allowance(*owner, *spender).call().await?;
 
-                println!("Allowance: {}", Scm::new(allowance));
+                println!("Allowance: {}", token.amount(allowance));
             }
 
             Self::Approve { spender, value } => {
+                let value = value.value(token)?;
+
                 contract
-                    .approve(*spender, value.as_inner())
+                    .approve(*spender, value)
                     .from(account)
                     .send()
-                    .await
-                    .expect("approve failed");
+                    .await?;
 
                 println!("Done");
             }
         }
+
+        Ok(())
     }
+}
 
-    async fn print_balance(address: Address, contract: &crate::contracts::SCM) {
-        let balance = contract
-            .balance_of(address)
-            .call()
-            .await
-            .expect("balance fetch failed");
+/// Check that a transfer of `funds` will go through, given the owner's
+/// balance and, if we're spending someone else's tokens, our allowance.
+fn check_transfer(
+    token: &Token,
+    balance: U256,
+    allowance: Option<U256>,
+    funds: U256,
+) -> Result<(), String> {
+    if balance < funds {
+        return Err(format!(
+            "insufficient balance: have {}, need {}",
+            token.amount(balance),
+            token.amount(funds)
+        ));
+    }
 
-        println!("SCM balance: {}", Scm::new(balance));
+    match allowance {
+        Some(allowance) if allowance < funds => Err(format!(
+            "allowance exhausted: allowed to spend {}, need {}",
+            token.amount(allowance),
+            token.amount(funds)
+        )),
+        _ => Ok(()),
     }
 }
 
+async fn print_balance(
+    address: Address,
+    contract: &crate::contracts::SCM,
+    token: &Token,
+) -> Result<()> {
+    let balance = contract.balance_of(address).call().await?;
+
+    println!("Balance: {}", token.amount(balance));
+
+    Ok(())
+}
+
 #[derive(structopt::StructOpt)]
-#[structopt(about = "Manage wrapped ethereum tokens")]
-pub enum WethCommand {
-    #[structopt(about = "Get balance of the given wallet")]
-    Balance {
-        #[structopt(help = "Account we're fetching balance for (uses your account by default)")]
-        address: Option<Address>,
-    },
+#[structopt(about = "Manage any ERC20 token deployed on the network")]
+pub struct TokenCommand {
+    #[structopt(long, help = "Address of the token contract")]
+    address: Address,
 
-    #[structopt(about = "Transfer funds between accounts")]
-    Transfer {
-        #[structopt(help = "Where are we transferring funds to")]
-        recipient: Address,
-        #[structopt(help = "Amount of funds we are transferring")]
-        funds: Eth,
-        #[structopt(
-            long,
-            help = "Where are we transferring funds from (uses your account by default)"
-        )]
-        owner: Option<Address>,
-    },
+    #[structopt(subcommand)]
+    command: Erc20Command,
+}
 
-    #[structopt(about = "Check allowance for the given owner-spender pair")]
-    Allowance {
-        #[structopt(help = "Who owns tokens")]
-        owner: Address,
-        #[structopt(help = "Who will be allowed to spend tokens")]
-        spender: Address,
-    },
+impl TokenCommand {
+    pub async fn invoke(&self, account: Account, web3: &Web3<SigningTransport>) -> Result<()> {
+        let contract = crate::contracts::SCM::at(web3, self.address);
+        let token = crate::contracts::get_token(web3, &contract).await?;
+
+        self.command
+            .invoke(
+                account,
+                web3,
+                &contract,
+                &AddressSource::CommandLine,
+                &token,
+            )
+            .await
+    }
+}
 
-    #[structopt(about = "Allow some other user to withdraw funds from your account")]
-    Approve {
-        #[structopt(help = "Who is allowed to withdraw funds")]
-        spender: Address,
-        #[structopt(
-            help = "Amount of funds they are allowed to withdraw (overrides previous allowance)"
-        )]
-        value: Eth,
-    },
+#[derive(structopt::StructOpt)]
+#[structopt(about = "Manage SCM tokens")]
+pub enum ScmCommand {
+    #[structopt(flatten)]
+    Erc20(Erc20Command),
+}
+
+impl ScmCommand {
+    pub async fn invoke(&self, account: Account, web3: &Web3<SigningTransport>) -> Result<()> {
+        let (contract_address, source) = crate::contracts::get_scm_address(web3).await?;
+        let contract = crate::contracts::SCM::at(web3, contract_address);
+        let token = crate::contracts::get_token(web3, &contract).await?;
+
+        match self {
+            Self::Erc20(command) => {
+                command
+                    .invoke(account, web3, &contract, &source, &token)
+                    .await
+            }
+        }
+    }
+}
+
+#[derive(structopt::StructOpt)]
+#[structopt(about = "Manage wrapped ethereum tokens")]
+pub enum WethCommand {
+    #[structopt(flatten)]
+    Erc20(Erc20Command<EthAmount>),
 
     #[structopt(about = "Wrap ether")]
     Deposit {
         #[structopt(help = "Amount of ether to wrap")]
-        amount: Eth,
+        amount: EthAmount,
     },
 
     #[structopt(about = "Unwrap ether")]
     Withdraw {
         #[structopt(help = "Amount of ether to unwrap")]
-        amount: Eth,
+        amount: EthAmount,
     },
 }
 
 impl WethCommand {
-    pub async fn invoke(&self, account: Account, web3: &Web3<Http>) {
+    pub async fn invoke(&self, account: Account, web3: &Web3<SigningTransport>) -> Result<()> {
         let account_address = account.address();
 
-        let contract_address = crate::contracts::get_weth_address(web3).await;
+        let (contract_address, source) = crate::contracts::get_weth_address(web3).await?;
         let contract = crate::contracts::WETH9::at(web3, contract_address);
+        let erc20 = crate::contracts::SCM::at(web3, contract_address);
+        let token = Token::eth();
 
         match self {
-            Self::Balance { address } => {
-                Self::print_balance(address.unwrap_or(account_address), &contract).await;
+            Self::Erc20(command) => {
+                command
+                    .invoke(account, web3, &erc20, &source, &token)
+                    .await?
             }
 
-            Self::Transfer {
-                recipient,
-                funds,
-                owner,
+            Self::Deposit {
+                amount: EthAmount(amount),
             } => {
                 contract
-                    .transfer_from(
-                        owner.unwrap_or(account_address),
-                        *recipient,
-                        funds.as_inner(),
-                    )
+                    .deposit()
                     .from(account)
+                    .value(*amount)
                     .send()
-                    .await
-                    .expect("transfer failed");
+                    .await?;
 
                 println!("Done");
-                Self::print_balance(account_address, &contract).await;
-            }
-
-            Self::Allowance { owner, spender } => {
-                let allowance = contract
-                    .allowance(*owner, *spender)
-                    .call()
-                    .await
-                    .expect("allowance fetch failed");
-
-                println!("Allowance: {}", Eth::new(allowance));
+                print_balance(account_address, &erc20, &token).await?;
             }
 
-            Self::Approve { spender, value } => {
-                contract
-                    .approve(*spender, value.as_inner())
-                    .from(account)
-                    .send()
-                    .await
-                    .expect("approve failed");
+            Self::Withdraw {
+                amount: EthAmount(amount),
+            } => {
+                contract.withdraw(*amount).from(account).send().await?;
 
                 println!("Done");
+                print_balance(account_address, &erc20, &token).await?;
             }
+        }
 
-            Self::Deposit { amount } => {
-                contract
-                    .deposit()
-                    .from(account)
-                    .value(amount.as_inner())
-                    .send()
-                    .await
-                    .expect("deposit failed");
+        Ok(())
+    }
+}
 
-                println!("Done");
-                Self::print_balance(account_address, &contract).await;
-            }
+#[cfg(test)]
+mod test_transfer {
+    use super::*;
 
-            Self::Withdraw { amount } => {
-                contract
-                    .withdraw(amount.as_inner())
-                    .from(account)
-                    .send()
-                    .await
-                    .expect("withdraw failed");
+    #[test]
+    fn check_transfer_balance() {
+        let token = Token::new("TKN", 0);
 
-                println!("Done");
-                Self::print_balance(account_address, &contract).await;
-            }
-        }
+        assert_eq!(check_transfer(&token, 10.into(), None, 10.into()), Ok(()));
+        assert_eq!(
+            check_transfer(&token, 5.into(), None, 10.into()),
+            Err("insufficient balance: have 5tkn, need 10tkn".to_string())
+        );
     }
 
-    async fn print_balance(address: Address, contract: &crate::contracts::WETH9) {
-        let balance = contract
-            .balance_of(address)
-            .call()
-            .await
-            .expect("balance fetch failed");
-
-        println!("WETH balance: {}", Eth::new(balance));
+    #[test]
+    fn check_transfer_allowance() {
+        let token = Token::new("TKN", 0);
+
+        assert_eq!(
+            check_transfer(&token, 10.into(), Some(10.into()), 10.into()),
+            Ok(())
+        );
+        assert_eq!(
+            check_transfer(&token, 10.into(), Some(3.into()), 10.into()),
+            Err("allowance exhausted: allowed to spend 3tkn, need 10tkn".to_string())
+        );
+        assert_eq!(
+            check_transfer(&token, 5.into(), Some(3.into()), 10.into()),
+            Err("insufficient balance: have 5tkn, need 10tkn".to_string())
+        );
     }
 }
@@ -0,0 +1,156 @@
+use ethcontract::errors::{EventError, ExecutionError, MethodError};
+use std::fmt::{Display, Formatter};
+
+use crate::cli::AmountParseError;
+use crate::revert::Revert;
+
+/// Result of running a CLI command.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error that stops a CLI command.
+///
+/// Errors are split into classes, each with its own exit code, so that
+/// scripts can tell a typo in the command line from an unreachable node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    /// Environment is misconfigured: missing account, invalid contract
+    /// address, unknown network and so on.
+    Config(String),
+
+    /// Node is unreachable or responded with something we don't understand.
+    Transport(String),
+
+    /// Contract rejected our call or transaction.
+    Revert(Revert),
+
+    /// Contract would reject our transaction, so we didn't send it.
+    /// Exits with the same code as the revert it predicts.
+    Rejected(Revert, String),
+
+    /// Command line arguments are invalid, or ask for something that
+    /// can't be done, like transferring more tokens than we have.
+    Input(String),
+
+    /// Waiting took longer than the user allowed.
+    Timeout(String),
+
+    /// User interrupted the command with Ctrl-C.
+    Interrupted(String),
+
+    /// `ico audit` found broken invariants.
+    Unhealthy(String),
+}
+
+impl Error {
+    /// Exit code the CLI uses when a command fails with this error.
+    ///
+    /// Reverts use a separate code for every known reason,
+    /// see [`Revert::exit_code`].
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => 2,
+            Error::Input(_) => 3,
+            Error::Transport(_) => 4,
+            Error::Timeout(_) => 5,
+            Error::Unhealthy(_) => 6,
+            Error::Interrupted(_) => 130,
+            Error::Revert(revert) | Error::Rejected(revert, _) => revert.exit_code(),
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Config(message) => write!(f, "configuration error: {}", message),
+            Error::Transport(message) => write!(f, "node error: {}", message),
+            Error::Revert(revert) => write!(f, "rejected by contract: {}", revert),
+            Error::Rejected(_, message) => write!(f, "{}", message),
+            Error::Input(message) => write!(f, "{}", message),
+            Error::Timeout(message) => write!(f, "timed out: {}", message),
+            Error::Interrupted(message) => write!(f, "interrupted: {}", message),
+            Error::Unhealthy(message) => write!(f, "audit failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<Revert> for Error {
+    fn from(revert: Revert) -> Self {
+        Error::Revert(revert)
+    }
+}
+
+impl From<MethodError> for Error {
+    fn from(err: MethodError) -> Self {
+        match Revert::from_error(&err) {
+            Some(revert) => Error::Revert(revert),
+            None => Error::Transport(err.to_string()),
+        }
+    }
+}
+
+impl From<ExecutionError> for Error {
+    fn from(err: ExecutionError) -> Self {
+        match Revert::from_execution_error(&err) {
+            Some(revert) => Error::Revert(revert),
+            None => Error::Transport(err.to_string()),
+        }
+    }
+}
+
+impl From<EventError> for Error {
+    fn from(err: EventError) -> Self {
+        err.inner.into()
+    }
+}
+
+impl From<ethcontract::web3::Error> for Error {
+    fn from(err: ethcontract::web3::Error) -> Self {
+        Error::Transport(err.to_string())
+    }
+}
+
+impl From<AmountParseError> for Error {
+    fn from(err: AmountParseError) -> Self {
+        Error::Input(format!("invalid amount: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod test_error {
+    use super::*;
+
+    #[test]
+    fn revert_from_method_error() {
+        let err = MethodError::from_parts(
+            "claim()".to_string(),
+            ExecutionError::Revert(Some("no SCM tokens to claim".to_string())),
+        );
+        assert_eq!(Error::from(err), Error::Revert(Revert::NothingToClaim));
+
+        let err = MethodError::from_parts("claim()".to_string(), ExecutionError::InvalidOpcode);
+        assert!(matches!(Error::from(err), Error::Transport(_)));
+    }
+
+    #[test]
+    fn exit_codes_do_not_overlap() {
+        let classes = [
+            Error::Config(String::new()),
+            Error::Input(String::new()),
+            Error::Transport(String::new()),
+            Error::Timeout(String::new()),
+            Error::Interrupted(String::new()),
+            Error::Unhealthy(String::new()),
+        ];
+
+        let reverts = Error::Revert(Revert::Unknown).exit_code()
+            ..=Error::Revert(Revert::ApprovingZeroAddress).exit_code();
+
+        for class in &classes {
+            assert!(class.exit_code() > 1);
+            assert!(!reverts.contains(&class.exit_code()));
+        }
+    }
+}
@@ -1,9 +1,20 @@
 use ethcontract::prelude::*;
 
-use crate::cli::{Currency, Eth, Scm};
-use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use crate::audit::AuditFacts;
+use crate::cli::{Duration, DurationParseError, EthAmount, OutputFormat, ScmAmount, Token};
+use crate::contracts::ico::event_data::Fund;
+use crate::contracts::ico::Event as IcoEvent;
+use crate::error::{Error, Result};
+use crate::revert::Revert;
+use crate::signer::SigningTransport;
+use crate::snapshot::{IcoSnapshot, IcoState};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use ethcontract::batch::CallBatch;
+use ethcontract::contract::ParseLog as _;
+use ethcontract::web3::types::Log;
 use futures::StreamExt as _;
+use std::cell::Cell;
+use std::io::{IsTerminal as _, Write as _};
 
 #[derive(structopt::StructOpt)]
 #[structopt(about = "Participate in SCM ICO")]
@@ -11,6 +22,20 @@ pub enum IcoCommand {
     #[structopt(about = "Get status of the ICO")]
     Info,
 
+    #[structopt(about = "Check ICO invariants, exit with an error if any of them is broken")]
+    Audit,
+
+    #[structopt(about = "List everyone who has contributed to the ICO")]
+    Contributors {
+        #[structopt(
+            long,
+            default_value = "table",
+            possible_values = OutputFormat::VARIANTS,
+            help = "Output format; csv and json give amounts in wei and asc"
+        )]
+        format: OutputFormat,
+    },
+
     #[structopt(about = "Get number of SCM tokens available to the given user")]
     Balance {
         #[structopt(help = "Account we're fetching balance for (uses your account by default)")]
@@ -19,262 +44,1677 @@ pub enum IcoCommand {
         eth: bool,
     },
 
+    #[structopt(about = "Calculate how many SCM a contribution buys, without sending anything")]
+    Quote {
+        #[structopt(
+            long,
+            conflicts_with = "eth",
+            help = "Number of SCM tokens to buy, quote ETH needed for them"
+        )]
+        scm: Option<ScmAmount>,
+        #[structopt(
+            required_unless = "scm",
+            help = "Number of ETH tokens to contribute to the ICO"
+        )]
+        eth: Option<EthAmount>,
+    },
+
     #[structopt(about = "Buy SCM")]
     Fund {
         #[structopt(long, help = "Wrap and approve eth if you don't have enough of it")]
         wrap_weth: bool,
         #[structopt(long, help = "Ensure that ICO is authorized to spend WETH")]
         approve_weth: bool,
+        #[structopt(
+            long,
+            conflicts_with = "approve-unlimited",
+            help = "Allow ICO to spend this much WETH instead of the exact contribution"
+        )]
+        approve: Option<EthAmount>,
+        #[structopt(long, help = "Allow ICO to spend any amount of WETH")]
+        approve_unlimited: bool,
+        #[structopt(
+            long,
+            help = "If there are not enough SCM left, buy whatever is left instead of failing"
+        )]
+        any: bool,
         #[structopt(help = "Number of ETH tokens to contribute to the ICO")]
-        funds: Eth,
+        funds: EthAmount,
     },
 
     #[structopt(about = "Claim purchased SCM")]
     Claim {
         #[structopt(long, help = "If ICO is not finished, wait for it")]
         wait: bool,
+        #[structopt(flatten)]
+        wait_options: WaitOptions,
     },
 
     #[structopt(about = "Wait for ICO to finish")]
-    Wait,
+    Wait {
+        #[structopt(flatten)]
+        wait_options: WaitOptions,
+    },
+}
+
+#[derive(structopt::StructOpt)]
+pub struct WaitOptions {
+    #[structopt(long, help = "Give up waiting after this long, i.e. `30m` or `1h 30m`")]
+    timeout: Option<Duration>,
+
+    #[structopt(
+        long,
+        parse(try_from_str = parse_poll_interval),
+        help = "How often to check whether the ICO has closed or finished [default: 5s]"
+    )]
+    poll_interval: Option<Duration>,
+
+    #[structopt(
+        long,
+        help = "Blocks to wait for on top of the one that closed the ICO [default: 0]"
+    )]
+    confirmations: Option<u64>,
+}
+
+impl WaitOptions {
+    fn poll_interval(&self) -> std::time::Duration {
+        self.poll_interval.unwrap_or(Duration(5)).into()
+    }
+
+    fn confirmations(&self) -> u64 {
+        self.confirmations.unwrap_or(0)
+    }
+
+    /// Whether any of the options was given on the command line.
+    fn is_given(&self) -> bool {
+        self.timeout.is_some() || self.poll_interval.is_some() || self.confirmations.is_some()
+    }
+}
+
+fn parse_poll_interval(s: &str) -> Result<Duration, String> {
+    match s
+        .parse()
+        .map_err(|err: DurationParseError| err.to_string())?
+    {
+        Duration(0) => Err("poll interval must be at least 1s".to_string()),
+        interval => Ok(interval),
+    }
 }
 
 impl IcoCommand {
-    pub async fn invoke(&self, account: Account, web3: &Web3<Http>) {
-        let contract_address = crate::contracts::get_ico_address(web3).await;
+    pub async fn invoke(&self, account: Account, web3: &Web3<SigningTransport>) -> Result<()> {
+        let (contract_address, _) = crate::contracts::get_ico_address(web3).await?;
         let contract = crate::contracts::ICO::at(web3, contract_address);
 
+        let eth = Token::eth();
+        let scm = Token::scm();
+
         match self {
             IcoCommand::Info => {
-                let current_block = web3.eth().block_number().await.unwrap();
-                let current_block = BlockId::Number(BlockNumber::Number(current_block));
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+
+                // Fund events may be out of reach, i.e. when the node limits
+                // log queries; the rest of the info doesn't depend on them.
+                let funds = query_funds(web3, &contract, &snapshot).await;
+
+                println!("State: {}", snapshot.state);
+                println!("Target: {}", eth.amount(snapshot.target));
+                match funds {
+                    Ok(funds) => {
+                        let raised = funds.iter().fold(U256::zero(), |raised, fund| {
+                            raised.saturating_add(fund.eth_used)
+                        });
+                        let contributors = crate::contributors::tally(&funds).len();
+
+                        println!("Raised: {}", eth.amount(raised));
+                        println!("Progress: {}", progress_bar(raised, snapshot.target));
+                        println!("Contributors: {}", contributors);
+                    }
+                    Err(err) => {
+                        println!("Raised: unavailable, can't query Fund events: {}", err);
+                        println!("Progress: unavailable");
+                        println!("Contributors: unavailable");
+                    }
+                }
+                println!("Left ETH: {}", eth.amount(snapshot.left_eth));
+                println!("Left SCM: {}", scm.amount(snapshot.left_scm));
+                println!("Rate: {} SCM per ETH", snapshot.rate);
+                println!(
+                    "Hold duration: {}",
+                    Duration(snapshot.hold_duration.low_u64())
+                );
+                println!("ICO: {:?}", contract_address);
+                println!("SCM: {:?}", snapshot.scm);
+                println!("WETH: {:?}", snapshot.weth);
+
+                if let Some(close_time) = snapshot.close_time {
+                    println!("Close time: {}", local_time(close_time));
+                }
+
+                if let Some(finish_time) = snapshot.finish_time {
+                    if snapshot.state == IcoState::Finished {
+                        println!("Finish time: {}", local_time(finish_time));
+                    } else {
+                        println!(
+                            "Finish time: {} (finishes in {})",
+                            local_time(finish_time),
+                            Duration(finish_time.saturating_sub(snapshot.timestamp))
+                        );
+                    }
+                }
+            }
+
+            IcoCommand::Audit => {
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+                let funds = query_funds(web3, &contract, &snapshot).await?;
+                let contributors = crate::contributors::tally(&funds);
+                let scm_contract = crate::contracts::SCM::at(web3, snapshot.scm);
 
                 let mut batch = CallBatch::new(web3.transport());
 
-                let state = contract.state().block(current_block).batch_call(&mut batch);
-                let left_eth = contract
-                    .left_eth()
-                    .block(current_block)
+                let balances: Vec<_> = contributors
+                    .iter()
+                    .map(|contributor| {
+                        contract
+                            .balance_scm(contributor.buyer)
+                            .block(snapshot.block())
+                            .batch_call(&mut batch)
+                    })
+                    .collect();
+                let ico_scm_balance = scm_contract
+                    .balance_of(contract_address)
+                    .block(snapshot.block())
                     .batch_call(&mut batch);
-                let left_scm = contract
-                    .left_scm()
-                    .block(current_block)
+                let total_supply = scm_contract
+                    .total_supply()
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+                let target_scm = contract
+                    .to_scm(snapshot.target)
+                    .block(snapshot.block())
                     .batch_call(&mut batch);
-                let scm = contract.scm().block(current_block).batch_call(&mut batch);
-                let weth = contract.weth().block(current_block).batch_call(&mut batch);
 
                 batch.execute_all(100).await;
 
-                let state = state.await.expect("state call failed");
-                match state {
-                    0x0 => println!("State: Ongoing"),
-                    0x1 => println!("State: Closed"),
-                    0x2 => println!("State: Finished"),
-                    unknown => println!("State: Unknown ({})", unknown),
+                let mut claimable = U256::zero();
+                for balance in balances {
+                    claimable = claimable.saturating_add(balance.await?);
+                }
+
+                let facts = AuditFacts {
+                    target: snapshot.target,
+                    left_eth: snapshot.left_eth,
+                    left_scm: snapshot.left_scm,
+                    raised: funds.iter().fold(U256::zero(), |raised, fund| {
+                        raised.saturating_add(fund.eth_used)
+                    }),
+                    claimable,
+                    ico_scm_balance: ico_scm_balance.await?,
+                    total_supply: total_supply.await?,
+                    target_scm: target_scm.await?,
                 };
 
-                println!(
-                    "Left ETH: {}",
-                    Eth::new(left_eth.await.expect("left_eth call failed"))
-                );
-                println!(
-                    "Left SCM: {}",
-                    Scm::new(left_scm.await.expect("left_scm call failed"))
-                );
-                println!("ICO: {:?}", contract_address);
-                println!("SCM: {:?}", scm.await.expect("scm call failed"));
-                println!("WETH: {:?}", weth.await.expect("weth call failed"));
-
-                if state != 0 {
-                    let close_time = {
-                        let timestamp = contract
-                            .close_time()
-                            .block(current_block)
-                            .call()
-                            .await
-                            .expect("close_time call failed")
-                            .as_u64();
-                        Local.from_utc_datetime(&NaiveDateTime::from_timestamp(timestamp as i64, 0))
-                    };
-
-                    println!("Close time: {}", close_time);
-
-                    let finish_time = {
-                        let timestamp = contract
-                            .finish_time()
-                            .block(current_block)
-                            .call()
-                            .await
-                            .expect("close_time call failed")
-                            .as_u64();
-                        Local.from_utc_datetime(&NaiveDateTime::from_timestamp(timestamp as i64, 0))
-                    };
-
-                    println!("Finish time: {}", finish_time);
+                println!("Block: {}", snapshot.block_number);
+
+                let checks = crate::audit::audit(&facts);
+                for check in &checks {
+                    println!("{}", check);
+                }
+
+                let failed = checks.iter().filter(|check| !check.ok).count();
+                if failed > 0 {
+                    println!("Healthy: no");
+                    return Err(Error::Unhealthy(format!(
+                        "{} of {} checks failed",
+                        failed,
+                        checks.len()
+                    )));
+                }
+
+                println!("Healthy: yes");
+            }
+
+            IcoCommand::Contributors { format } => {
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+                let funds = query_funds(web3, &contract, &snapshot).await?;
+                let mut contributors = crate::contributors::tally(&funds);
+
+                let mut batch = CallBatch::new(web3.transport());
+
+                let balances: Vec<_> = contributors
+                    .iter()
+                    .map(|contributor| {
+                        contract
+                            .balance_eth(contributor.buyer)
+                            .block(snapshot.block())
+                            .batch_call(&mut batch)
+                    })
+                    .collect();
+
+                batch.execute_all(100).await;
+
+                for (contributor, balance) in contributors.iter_mut().zip(balances) {
+                    contributor.balance_eth = balance.await?;
+                }
+
+                let target = snapshot.target;
+                match format {
+                    OutputFormat::Table => {
+                        print!("{}", crate::contributors::table(&contributors, target))
+                    }
+                    OutputFormat::Csv => {
+                        print!("{}", crate::contributors::csv(&contributors, target))
+                    }
+                    OutputFormat::Json => {
+                        println!("{:#}", crate::contributors::json(&contributors, target))
+                    }
                 }
             }
 
-            IcoCommand::Balance { address, eth } => {
+            IcoCommand::Balance {
+                address,
+                eth: in_eth,
+            } => {
                 let address = address.unwrap_or(account.address());
 
-                if *eth {
-                    let balance = contract
-                        .balance_eth(address)
-                        .call()
-                        .await
-                        .expect("balance fetch failed");
-                    println!("ICO balance: {}", Eth::new(balance));
+                if *in_eth {
+                    let balance = contract.balance_eth(address).call().await?;
+                    println!("ICO balance: {}", eth.amount(balance));
                 } else {
-                    let balance = contract
-                        .balance_scm(address)
-                        .call()
-                        .await
-                        .expect("balance fetch failed");
-                    println!("ICO balance: {}", Scm::new(balance));
+                    let balance = contract.balance_scm(address).call().await?;
+                    println!("ICO balance: {}", scm.amount(balance));
                 };
             }
 
+            IcoCommand::Quote {
+                scm: scm_amount,
+                eth: eth_amount,
+            } => {
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+
+                let funds = match (eth_amount, scm_amount) {
+                    (Some(EthAmount(amount)), _) => *amount,
+                    (None, Some(ScmAmount(wanted))) => eth_for_scm(*wanted, snapshot.rate)
+                        .ok_or_else(|| {
+                            Error::Transport("ICO reports zero exchange rate".to_string())
+                        })?,
+                    (None, None) => unreachable!("structopt requires one of the amounts"),
+                };
+
+                let quote = quote(snapshot.state, snapshot.left_eth, funds);
+
+                let mut batch = CallBatch::new(web3.transport());
+
+                let purchased = contract
+                    .to_scm(quote.eth)
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+                let purchased_any = contract
+                    .to_scm(quote.spend)
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+
+                batch.execute_all(100).await;
+
+                let purchased = purchased.await?;
+                let purchased_any = purchased_any.await?;
+
+                println!(
+                    "{} buys {} at {} SCM per ETH",
+                    eth.amount(quote.eth),
+                    scm.amount(purchased),
+                    snapshot.rate
+                );
+
+                if snapshot.state != IcoState::Ongoing {
+                    println!("ICO is {}, it doesn't accept contributions", snapshot.state);
+                    return Ok(());
+                }
+
+                if quote.fits {
+                    println!(
+                        "Fits: yes, {} left to collect",
+                        eth.amount(snapshot.left_eth)
+                    );
+                } else {
+                    println!(
+                        "Fits: no, only {} left to collect, `ico fund` would fail",
+                        eth.amount(snapshot.left_eth)
+                    );
+                }
+
+                println!(
+                    "`ico fund --any` would spend {} and buy {}",
+                    eth.amount(quote.spend),
+                    scm.amount(purchased_any)
+                );
+                println!(
+                    "Closes the ICO: {}",
+                    if quote.closes { "yes" } else { "no" }
+                );
+            }
+
             IcoCommand::Fund {
                 wrap_weth,
                 approve_weth,
+                approve,
+                approve_unlimited,
+                any,
                 funds,
             } => {
-                let weth_address = contract.weth().call().await.unwrap();
-                let weth = crate::contracts::WETH9::at(web3, weth_address);
-
-                if *wrap_weth {
-                    let balance = weth
-                        .balance_of(account.address())
-                        .call()
-                        .await
-                        .expect("balance_of call failed");
-
-                    if balance < funds.as_inner() {
-                        println!("Wrapping WETH");
-                        weth.deposit()
-                            .from(account.clone())
-                            .value(funds.as_inner())
-                            .send()
-                            .await
-                            .expect("deposit failed");
-                    } else {
-                        println!("WETH balance is sufficient, no need to wrap more");
+                let funds = funds.0;
+                let approval = match approve {
+                    Some(EthAmount(amount)) => Approval::Amount(*amount),
+                    None if *approve_unlimited => Approval::Unlimited,
+                    None => Approval::Exact,
+                };
+
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+                let weth = crate::contracts::WETH9::at(web3, snapshot.weth);
+
+                let mut batch = CallBatch::new(web3.transport());
+
+                let balance = weth
+                    .balance_of(account.address())
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+                let allowance = weth
+                    .allowance(account.address(), contract_address)
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+
+                batch.execute_all(100).await;
+
+                let left_eth = snapshot.left_eth;
+                let balance = balance.await?;
+                let allowance = allowance.await?;
+
+                // `fundAny` only spends what's left, so there's no point
+                // in wrapping and approving more than that.
+                let needed = if *any {
+                    if left_eth < funds {
+                        println!("Only {} left to collect", eth.amount(left_eth));
                     }
+                    funds.min(left_eth)
+                } else {
+                    funds
+                };
+
+                let approve_weth = *approve_weth || *wrap_weth || approval != Approval::Exact;
+                let plan = plan_fund(
+                    needed,
+                    wrap_weth.then_some(balance),
+                    approve_weth.then_some(allowance),
+                    approval,
+                )
+                .map_err(Error::Input)?;
+
+                check_fund(snapshot.state, left_eth, needed, balance, allowance, &plan)?;
+
+                match plan.wrap {
+                    Some(wrap) => println!("Will wrap {}", eth.amount(wrap)),
+                    None if *wrap_weth => {
+                        println!("WETH balance is sufficient, no need to wrap more")
+                    }
+                    None => (),
                 }
 
-                if *approve_weth || *wrap_weth {
-                    let allowance = weth
-                        .allowance(account.address(), contract_address)
-                        .call()
-                        .await
-                        .expect("balance_of call failed");
-
-                    if allowance < funds.as_inner() {
-                        println!("Approving WETH");
-                        weth.approve(contract_address, U256::exp10(18) * 10)
-                            .from(account.clone())
-                            .send()
-                            .await
-                            .expect("approve failed");
-                    } else {
-                        println!("WETH allowance is sufficient, no need to approve more");
+                match plan.approve {
+                    Some(approve) if approve == U256::MAX => {
+                        println!("Will approve unlimited WETH")
+                    }
+                    Some(approve) => println!("Will approve {}", eth.amount(approve)),
+                    None if approve_weth => {
+                        println!("WETH allowance is sufficient, no need to approve more")
                     }
+                    None => (),
+                }
+
+                if let Some(wrap) = plan.wrap {
+                    println!("Wrapping WETH");
+                    weth.deposit()
+                        .from(account.clone())
+                        .value(wrap)
+                        .send()
+                        .await?;
                 }
 
-                contract
-                    .fund(funds.as_inner())
-                    .from(account.clone())
-                    .send()
-                    .await
-                    .expect("fund call failed");
+                if let Some(approve) = plan.approve {
+                    println!("Approving WETH");
+                    weth.approve(contract_address, approve)
+                        .from(account.clone())
+                        .send()
+                        .await?;
+                }
+
+                let result = if *any {
+                    contract
+                        .fund_any(funds)
+                        .from(account.clone())
+                        .send()
+                        .await?
+                } else {
+                    contract.fund(funds).from(account.clone()).send().await?
+                };
+
+                let fund = result.as_receipt().and_then(|receipt| {
+                    find_fund_event(&receipt.logs, contract_address, account.address())
+                });
 
                 println!("Done");
 
-                let balance = contract
-                    .balance_scm(account.address())
-                    .call()
-                    .await
-                    .expect("balance fetch failed");
+                if let Some(fund) = fund {
+                    println!("Spent: {}", eth.amount(fund.eth_used));
+                    println!("Purchased: {}", scm.amount(fund.scm_purchased));
+                }
+
+                let balance = contract.balance_scm(account.address()).call().await?;
 
-                println!("ICO balance: {}", Scm::new(balance));
+                println!("ICO balance: {}", scm.amount(balance));
             }
 
-            IcoCommand::Claim { wait } => {
-                if *wait {
-                    wait_finish(web3, &contract).await;
+            IcoCommand::Claim { wait, wait_options } => {
+                if !*wait && wait_options.is_given() {
+                    return Err(Error::Input(
+                        "--timeout, --poll-interval and --confirmations require --wait".to_string(),
+                    ));
                 }
 
-                contract
-                    .claim()
-                    .from(account.clone())
-                    .send()
-                    .await
-                    .expect("fund call failed");
+                let snapshot = IcoSnapshot::fetch(web3, &contract).await?;
+                let scm_contract = crate::contracts::SCM::at(web3, snapshot.scm);
+
+                let mut batch = CallBatch::new(web3.transport());
+
+                let claimable = contract
+                    .balance_scm(account.address())
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+                let balance_before = scm_contract
+                    .balance_of(account.address())
+                    .block(snapshot.block())
+                    .batch_call(&mut batch);
+
+                batch.execute_all(100).await;
+
+                let claimable = claimable.await?;
+                let balance_before = balance_before.await?;
+
+                let pending = match snapshot.state {
+                    IcoState::Closed => pending_state(&contract).await?,
+                    state => state,
+                };
+
+                match claim_status(&snapshot, pending, claimable) {
+                    ClaimStatus::NothingToClaim => {
+                        println!("Nothing to claim");
+                        return Ok(());
+                    }
+                    ClaimStatus::Ongoing if !*wait => {
+                        println!(
+                            "{} will be claimable after ICO is finished",
+                            scm.amount(claimable)
+                        );
+                        return Ok(());
+                    }
+                    ClaimStatus::ClaimableIn(seconds) if !*wait => {
+                        println!(
+                            "{} claimable in {}",
+                            scm.amount(claimable),
+                            Duration(seconds)
+                        );
+                        return Ok(());
+                    }
+                    ClaimStatus::Claimable => (),
+                    _ => wait_finish(web3, &contract, wait_options).await?,
+                }
+
+                println!("Claiming {}", scm.amount(claimable));
+
+                contract.claim().from(account.clone()).send().await?;
 
                 println!("Done");
 
-                let scm_address = contract.scm().call().await.unwrap();
-                let scm = crate::contracts::SCM::at(web3, scm_address);
+                let balance_after = scm_contract.balance_of(account.address()).call().await?;
 
-                let balance = scm
-                    .balance_of(account.address())
-                    .call()
-                    .await
-                    .expect("balance fetch failed");
+                println!("SCM balance before: {}", scm.amount(balance_before));
+                println!("SCM balance after: {}", scm.amount(balance_after));
+            }
 
-                println!("SCM balance: {}", Scm::new(balance));
+            IcoCommand::Wait { wait_options } => {
+                wait_finish(web3, &contract, wait_options).await?;
             }
+        }
+
+        Ok(())
+    }
+}
+
+/// How much WETH to approve when the ICO's allowance is insufficient.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Approval {
+    /// Approve exactly the amount we're about to spend.
+    Exact,
+
+    /// Approve the given amount.
+    Amount(U256),
+
+    /// Approve the maximum possible amount, so that we never have to approve again.
+    Unlimited,
+}
 
-            IcoCommand::Wait => {
-                wait_finish(web3, &contract).await;
+/// WETH transactions that should be sent before funding the ICO.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+struct FundPlan {
+    /// Amount of ETH to wrap.
+    wrap: Option<U256>,
+
+    /// New allowance for the ICO.
+    approve: Option<U256>,
+}
+
+/// Figure out how much to wrap and approve to spend `needed` WETH.
+///
+/// `balance` and `allowance` are only given if we're asked to wrap
+/// and approve WETH, respectively.
+fn plan_fund(
+    needed: U256,
+    balance: Option<U256>,
+    allowance: Option<U256>,
+    approval: Approval,
+) -> Result<FundPlan, String> {
+    let wrap = balance
+        .filter(|&balance| balance < needed)
+        .map(|balance| needed - balance);
+
+    let approve = match allowance {
+        Some(allowance) if allowance < needed => Some(match approval {
+            Approval::Exact => needed,
+            Approval::Amount(amount) if amount < needed => {
+                return Err(format!(
+                    "approval is too small: asked to approve {}, need {}",
+                    Token::eth().amount(amount),
+                    Token::eth().amount(needed)
+                ))
             }
+            Approval::Amount(amount) => amount,
+            Approval::Unlimited => U256::MAX,
+        }),
+        _ => None,
+    };
+
+    Ok(FundPlan { wrap, approve })
+}
+
+/// Check that `ICO.fund` will accept `needed` WETH once the planned
+/// transactions are sent, mirroring its `require` statements.
+///
+/// Errors say what exactly is missing and exit with the code of the revert
+/// the contract would report.
+fn check_fund(
+    state: IcoState,
+    left_eth: U256,
+    needed: U256,
+    balance: U256,
+    allowance: U256,
+    plan: &FundPlan,
+) -> Result<()> {
+    let eth = Token::eth();
+    let wrapped = match plan.wrap {
+        Some(wrap) => format!(" (after wrapping {})", eth.amount(wrap)),
+        None => String::new(),
+    };
+    let balance = balance.saturating_add(plan.wrap.unwrap_or_default());
+    let allowance = plan.approve.unwrap_or(allowance);
+
+    let (revert, message) = if state != IcoState::Ongoing {
+        (Revert::IcoClosed, "ICO is closed".to_string())
+    } else if needed > left_eth {
+        (
+            Revert::NotEnoughTokensLeft,
+            format!(
+                "only {} left to collect, asked for {}",
+                eth.amount(left_eth),
+                eth.amount(needed)
+            ),
+        )
+    } else if balance < needed {
+        (
+            Revert::NotEnoughWeth,
+            format!(
+                "not enough WETH: have {}, need {}{}",
+                eth.amount(balance),
+                eth.amount(needed),
+                wrapped
+            ),
+        )
+    } else if allowance < needed {
+        (
+            Revert::NotAllowedToSpendWeth,
+            format!(
+                "ICO is allowed to spend only {} of WETH, need {}",
+                eth.amount(allowance),
+                eth.amount(needed)
+            ),
+        )
+    } else {
+        return Ok(());
+    };
+
+    Err(Error::Rejected(revert, message))
+}
+
+/// What the ICO would do with a contribution.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Quote {
+    /// Contribution we're quoting.
+    eth: U256,
+
+    /// How much of it `fundAny` would actually spend.
+    spend: U256,
+
+    /// Whether `fund` would accept the whole contribution.
+    fits: bool,
+
+    /// Whether the contribution buys all SCM that are left.
+    closes: bool,
+}
+
+/// Figure out what happens if `eth` is contributed to the ICO.
+fn quote(state: IcoState, left_eth: U256, eth: U256) -> Quote {
+    if state != IcoState::Ongoing {
+        return Quote {
+            eth,
+            spend: U256::zero(),
+            fits: false,
+            closes: false,
+        };
+    }
+
+    let spend = eth.min(left_eth);
+    Quote {
+        eth,
+        spend,
+        fits: eth <= left_eth,
+        closes: !spend.is_zero() && spend == left_eth,
+    }
+}
+
+/// ETH needed to buy at least `scm` tokens, the inverse of `ICO.toScm`.
+///
+/// Returns `None` if the rate is zero.
+fn eth_for_scm(scm: U256, rate: U256) -> Option<U256> {
+    if rate.is_zero() {
+        return None;
+    }
+
+    let eth = scm / rate;
+    Some(if (scm % rate).is_zero() { eth } else { eth + 1 })
+}
+
+/// Whether a user can claim their SCM right now.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum ClaimStatus {
+    /// User has no SCM tokens to claim.
+    NothingToClaim,
+
+    /// ICO is ongoing, so we don't know when it'll finish.
+    Ongoing,
+
+    /// ICO is closed and will finish in this many seconds.
+    ClaimableIn(u64),
+
+    /// ICO is finished, tokens can be claimed.
+    Claimable,
+}
+
+/// Figure out whether `claim` sent now will go through.
+///
+/// Besides the snapshot, takes the ICO state at the pending block,
+/// see [`pending_state`].
+fn claim_status(snapshot: &IcoSnapshot, pending: IcoState, claimable: U256) -> ClaimStatus {
+    if claimable.is_zero() {
+        return ClaimStatus::NothingToClaim;
+    }
+
+    match (snapshot.state, snapshot.finish_time) {
+        (IcoState::Finished, _) => ClaimStatus::Claimable,
+        _ if pending == IcoState::Finished => ClaimStatus::Claimable,
+        (_, Some(finish_time)) => {
+            ClaimStatus::ClaimableIn(finish_time.saturating_sub(snapshot.timestamp))
         }
+        (_, None) => ClaimStatus::Ongoing,
     }
 }
 
-async fn wait_finish(web3: &Web3<Http>, contract: &crate::contracts::ICO) {
-    let current_block = web3.eth().block_number().await.unwrap();
-    let state = contract
-        .state()
-        .block(BlockId::Number(BlockNumber::Number(current_block)))
-        .call()
-        .await
-        .unwrap();
+/// Fetch all `Fund` events from ICO deployment up to the snapshot's block.
+async fn query_funds(
+    web3: &Web3<SigningTransport>,
+    contract: &crate::contracts::ICO,
+    snapshot: &IcoSnapshot,
+) -> Result<Vec<Fund>> {
+    let deployment_block =
+        crate::contracts::get_ico_deployment_block(web3, contract.address()).await?;
+
+    let events = contract
+        .events()
+        .fund()
+        .from_block(deployment_block)
+        .to_block(BlockNumber::Number(snapshot.block_number))
+        .query()
+        .await?;
+
+    Ok(events.into_iter().map(|event| event.data).collect())
+}
+
+/// Width of the progress bar in `ico info`, in characters.
+const PROGRESS_BAR_WIDTH: u64 = 30;
+
+/// Render how much of the target is raised as `[#####-----] 50%`.
+fn progress_bar(raised: U256, target: U256) -> String {
+    let percent = if target.is_zero() {
+        100
+    } else {
+        (raised.saturating_mul(100.into()) / target)
+            .min(100.into())
+            .as_u64()
+    };
+
+    let filled = (percent * PROGRESS_BAR_WIDTH / 100) as usize;
+    let empty = PROGRESS_BAR_WIDTH as usize - filled;
+
+    format!("[{}{}] {}%", "#".repeat(filled), "-".repeat(empty), percent)
+}
+
+/// Convert a unix timestamp to local time.
+fn local_time(timestamp: u64) -> DateTime<Local> {
+    Local.from_utc_datetime(&NaiveDateTime::from_timestamp(timestamp as i64, 0))
+}
+
+/// Find the `Fund` event emitted by the ICO for the given buyer
+/// among transaction logs.
+fn find_fund_event(logs: &[Log], ico: Address, buyer: Address) -> Option<Fund> {
+    logs.iter()
+        .filter(|log| log.address == ico)
+        .filter_map(|log| IcoEvent::parse_log(log.clone().into()).ok())
+        .find_map(|event| match event {
+            IcoEvent::Fund(fund) if fund.buyer == buyer => Some(fund),
+            _ => None,
+        })
+}
+
+/// How far we've got while waiting for the ICO to finish.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum WaitProgress {
+    /// We haven't fetched ICO state yet.
+    Starting,
+
+    /// ICO is ongoing, this much ETH is left to collect.
+    Closing { left_eth: U256 },
+
+    /// ICO is closed, and the block that closed it has this many
+    /// confirmations out of the required number.
+    Confirming { confirmations: u64, required: u64 },
+
+    /// ICO is closed and will finish in this many seconds of chain time.
+    Finishing { seconds_left: u64 },
+}
+
+impl std::fmt::Display for WaitProgress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaitProgress::Starting => f.write_str("ICO state is not known yet"),
+            WaitProgress::Closing { left_eth } => write!(
+                f,
+                "ICO is ongoing, {} left to collect",
+                Token::eth().amount(*left_eth)
+            ),
+            WaitProgress::Confirming {
+                confirmations,
+                required,
+            } => write!(
+                f,
+                "ICO is closed, waiting for confirmations ({}/{})",
+                confirmations, required
+            ),
+            WaitProgress::Finishing { seconds_left: 0 } => {
+                f.write_str("ICO is closed and about to finish")
+            }
+            WaitProgress::Finishing { seconds_left } => {
+                write!(f, "ICO is closed, finishes in {}", Duration(*seconds_left))
+            }
+        }
+    }
+}
+
+/// Whether the block where we've seen the ICO closed can be relied upon.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum CloseStatus {
+    /// Block is no longer on the canonical chain.
+    Orphaned,
+
+    /// Block is on the canonical chain, but doesn't have enough
+    /// descendants yet.
+    Confirming { confirmations: u64 },
+
+    /// Block has enough descendants on the canonical chain.
+    Confirmed,
+}
+
+/// Check the block with the given number and hash against the canonical
+/// chain, given hash of the canonical block at that height and number
+/// of the latest block.
+fn close_status(
+    number: u64,
+    hash: H256,
+    canonical: Option<H256>,
+    latest_block: u64,
+    required: u64,
+) -> CloseStatus {
+    if canonical != Some(hash) {
+        return CloseStatus::Orphaned;
+    }
+
+    let confirmations = latest_block.saturating_sub(number);
+    if confirmations >= required {
+        CloseStatus::Confirmed
+    } else {
+        CloseStatus::Confirming { confirmations }
+    }
+}
+
+/// What to do once the block where we've seen the ICO closed is orphaned.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum AfterOrphan {
+    /// ICO is ongoing again, wait for the closing event.
+    WaitForClose,
+
+    /// Closing transaction was included in another block, query events
+    /// to find it: the event stream won't report blocks it has passed.
+    FindCloseBlock,
+}
+
+/// Decide how to recover from an orphaned closing block, given the ICO state
+/// at the new latest block.
+fn after_orphan(state: IcoState) -> AfterOrphan {
+    match state {
+        IcoState::Ongoing => AfterOrphan::WaitForClose,
+        IcoState::Closed | IcoState::Finished => AfterOrphan::FindCloseBlock,
+    }
+}
+
+/// Wait until the ICO is finished, giving up on timeout or Ctrl-C.
+async fn wait_finish(
+    web3: &Web3<SigningTransport>,
+    contract: &crate::contracts::ICO,
+    options: &WaitOptions,
+) -> Result<()> {
+    let progress = Cell::new(WaitProgress::Starting);
+
+    let timeout = async {
+        match options.timeout {
+            Some(timeout) => tokio::time::sleep(timeout.into()).await,
+            None => futures::future::pending().await,
+        }
+    };
+
+    let result = tokio::select! {
+        result = follow_ico(web3, contract, options, &progress) => result,
+        _ = timeout => Err(Error::Timeout(progress.get().to_string())),
+        _ = tokio::signal::ctrl_c() => Err(Error::Interrupted(progress.get().to_string())),
+    };
 
-    if state == 0 {
-        println!("Waiting for ICO to close");
-        contract
+    clear_progress();
+
+    if result.is_ok() {
+        println!("ICO is finished");
+    }
+
+    result
+}
+
+/// Poll the ICO until it's finished, reporting progress along the way.
+async fn follow_ico(
+    web3: &Web3<SigningTransport>,
+    contract: &crate::contracts::ICO,
+    options: &WaitOptions,
+    progress: &Cell<WaitProgress>,
+) -> Result<()> {
+    let poll_interval = options.poll_interval();
+
+    let mut snapshot = IcoSnapshot::fetch(web3, contract).await?;
+
+    // Number and hash of the block where we've seen the ICO closed.
+    let mut closed_in: Option<(u64, H256)> = None;
+
+    // If the ICO was closed before we started, its closing block still
+    // needs confirmations.
+    let ongoing = snapshot.state == IcoState::Ongoing;
+    if !ongoing && options.confirmations() > 0 {
+        closed_in = Some(find_close_block(web3, contract, &snapshot).await?);
+    }
+
+    if ongoing || closed_in.is_some() {
+        if ongoing {
+            println!("Waiting for ICO to close");
+        }
+
+        let mut closed = contract
             .events()
             .ico_closed()
-            .from_block(BlockNumber::Number(current_block))
+            .from_block(BlockNumber::Number(snapshot.block_number))
             .stream()
-            .boxed()
-            .next()
-            .await;
-        println!("ICO closed");
-    }
-
-    let finish_time = {
-        let timestamp = contract
-            .finish_time()
-            .call()
-            .await
-            .expect("finish_time call failed")
-            .as_u64();
-        let naive = NaiveDateTime::from_timestamp(timestamp as i64, 0);
-        Utc.from_utc_datetime(&naive)
-    };
-    let now = Utc::now();
+            .boxed();
+
+        loop {
+            if let Some((number, hash)) = closed_in {
+                let latest_block = web3.eth().block_number().await?.as_u64();
+                let canonical = web3
+                    .eth()
+                    .block(BlockId::Number(BlockNumber::Number(number.into())))
+                    .await?
+                    .and_then(|block| block.hash);
+
+                match close_status(
+                    number,
+                    hash,
+                    canonical,
+                    latest_block,
+                    options.confirmations(),
+                ) {
+                    CloseStatus::Confirmed => break,
+                    CloseStatus::Confirming { confirmations } => {
+                        progress.set(WaitProgress::Confirming {
+                            confirmations,
+                            required: options.confirmations(),
+                        });
+                    }
+                    CloseStatus::Orphaned => {
+                        clear_progress();
+                        snapshot = IcoSnapshot::fetch(web3, contract).await?;
+                        closed_in = match after_orphan(snapshot.state) {
+                            AfterOrphan::WaitForClose => {
+                                println!("Block that closed the ICO was orphaned, waiting again");
+                                None
+                            }
+                            AfterOrphan::FindCloseBlock => {
+                                println!("Block that closed the ICO was orphaned, looking again");
+                                Some(find_close_block(web3, contract, &snapshot).await?)
+                            }
+                        };
+                        continue;
+                    }
+                }
+            }
+
+            if closed_in.is_none() {
+                let left_eth = contract.left_eth().call().await?;
+                progress.set(WaitProgress::Closing { left_eth });
+            }
+
+            show_progress(progress.get());
+
+            tokio::select! {
+                event = closed.next() => match event {
+                    Some(event) => {
+                        let event = event?;
+                        match (event.data, event.meta) {
+                            (EventStatus::Added(_), Some(meta)) => {
+                                closed_in = Some((meta.block_number, meta.block_hash));
+                            }
+                            // Without metadata there's nothing to track.
+                            (EventStatus::Added(_), None) => break,
+                            (EventStatus::Removed(_), Some(meta))
+                                if closed_in.map(|(_, hash)| hash) == Some(meta.block_hash) =>
+                            {
+                                closed_in = None;
+                            }
+                            (EventStatus::Removed(_), _) => (),
+                        }
+                    }
+                    None => {
+                        let message = "event stream ended before ICO was closed";
+                        return Err(Error::Transport(message.to_string()));
+                    }
+                },
+                _ = tokio::time::sleep(poll_interval) => (),
+            }
+        }
+
+        clear_progress();
+        if ongoing {
+            println!("ICO closed");
+        } else {
+            println!("Block that closed the ICO is confirmed");
+        }
+
+        snapshot = IcoSnapshot::fetch(web3, contract).await?;
+    }
+
+    let finish_time = snapshot.finish_time.ok_or_else(|| {
+        Error::Transport("ICO is closed, but its finish time is unknown".to_string())
+    })?;
+
+    println!("ICO will finish on {}", local_time(finish_time));
+    println!("Waiting for ICO to finish");
+
+    // Follow chain time rather than the local clock: dev nodes can shift
+    // block timestamps, and local clock can drift.
+    loop {
+        progress.set(WaitProgress::Finishing {
+            seconds_left: finish_time.saturating_sub(snapshot.timestamp),
+        });
+        show_progress(progress.get());
+
+        if snapshot.state == IcoState::Finished
+            || pending_state(contract).await? == IcoState::Finished
+        {
+            break;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+        snapshot = IcoSnapshot::fetch(web3, contract).await?;
+    }
+
+    Ok(())
+}
+
+/// Find number and hash of the block where the ICO was closed,
+/// given a snapshot taken after that.
+async fn find_close_block(
+    web3: &Web3<SigningTransport>,
+    contract: &crate::contracts::ICO,
+    snapshot: &IcoSnapshot,
+) -> Result<(u64, H256)> {
+    let deployment_block =
+        crate::contracts::get_ico_deployment_block(web3, contract.address()).await?;
+
+    let events = contract
+        .events()
+        .ico_closed()
+        .from_block(deployment_block)
+        .to_block(BlockNumber::Number(snapshot.block_number))
+        .query()
+        .await?;
+
+    events
+        .iter()
+        .rev()
+        .find_map(|event| event.meta.as_ref())
+        .map(|meta| (meta.block_number, meta.block_hash))
+        .ok_or_else(|| Error::Transport("ICO is closed, but no IcoClosed event found".to_string()))
+}
+
+/// ICO state as a transaction sent right now would see it.
+///
+/// Nodes that mine blocks on demand won't produce a block past the finish
+/// time by themselves, but their pending block is built with the current
+/// time, so the ICO may already be finished there.
+async fn pending_state(contract: &crate::contracts::ICO) -> Result<IcoState> {
+    let pending_block = BlockId::Number(BlockNumber::Pending);
+    let state = contract.state().block(pending_block).call().await?;
+    IcoState::from_raw(state)
+}
 
-    if now < finish_time {
-        println!("ICO will finish on {}", finish_time.with_timezone(&Local));
-        println!("Waiting for ICO to finish");
-        tokio::time::sleep((finish_time - now).to_std().unwrap()).await;
+/// Overwrite the progress line, if stdout is a terminal.
+fn show_progress(progress: WaitProgress) {
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        let _ = write!(stdout, "\r\x1b[K{}", progress);
+        let _ = stdout.flush();
+    }
+}
+
+/// Erase the progress line, if stdout is a terminal.
+fn clear_progress() {
+    let mut stdout = std::io::stdout();
+    if stdout.is_terminal() {
+        let _ = write!(stdout, "\r\x1b[K");
+        let _ = stdout.flush();
+    }
+}
+
+#[cfg(test)]
+mod test_plan_fund {
+    use super::*;
+
+    #[test]
+    fn wrap_shortfall() {
+        let plan = plan_fund(10.into(), Some(3.into()), None, Approval::Exact);
+        assert_eq!(
+            plan,
+            Ok(FundPlan {
+                wrap: Some(7.into()),
+                approve: None
+            })
+        );
+
+        let plan = plan_fund(10.into(), Some(10.into()), None, Approval::Exact);
+        assert_eq!(plan, Ok(FundPlan::default()));
+
+        let plan = plan_fund(10.into(), None, None, Approval::Exact);
+        assert_eq!(plan, Ok(FundPlan::default()));
+    }
+
+    #[test]
+    fn approve_exact() {
+        let plan = plan_fund(10.into(), None, Some(3.into()), Approval::Exact);
+        assert_eq!(
+            plan,
+            Ok(FundPlan {
+                wrap: None,
+                approve: Some(10.into())
+            })
+        );
+
+        let plan = plan_fund(10.into(), None, Some(10.into()), Approval::Exact);
+        assert_eq!(plan, Ok(FundPlan::default()));
     }
 
-    while contract.state().call().await.unwrap() != 0x2 {
-        tokio::time::sleep(std::time::Duration::new(10, 0)).await;
+    #[test]
+    fn approve_amount() {
+        let plan = plan_fund(10.into(), None, Some(3.into()), Approval::Amount(15.into()));
+        assert_eq!(
+            plan,
+            Ok(FundPlan {
+                wrap: None,
+                approve: Some(15.into())
+            })
+        );
+
+        let plan = plan_fund(10.into(), None, Some(3.into()), Approval::Amount(5.into()));
+        assert_eq!(
+            plan,
+            Err("approval is too small: asked to approve 0.000000000000000005eth, need 0.000000000000000010eth".to_string())
+        );
+    }
+
+    #[test]
+    fn approve_unlimited() {
+        let plan = plan_fund(
+            10.into(),
+            Some(0.into()),
+            Some(3.into()),
+            Approval::Unlimited,
+        );
+        assert_eq!(
+            plan,
+            Ok(FundPlan {
+                wrap: Some(10.into()),
+                approve: Some(U256::MAX)
+            })
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_check_fund {
+    use super::*;
+
+    #[test]
+    fn fund_goes_through() {
+        let plan = FundPlan::default();
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                10.into(),
+                10.into(),
+                10.into(),
+                10.into(),
+                &plan
+            ),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn fund_reverts() {
+        let plan = FundPlan::default();
+        assert_eq!(
+            check_fund(
+                IcoState::Closed,
+                10.into(),
+                10.into(),
+                10.into(),
+                10.into(),
+                &plan
+            ),
+            Err(Error::Rejected(
+                Revert::IcoClosed,
+                "ICO is closed".to_string()
+            ))
+        );
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                5.into(),
+                10.into(),
+                10.into(),
+                10.into(),
+                &plan
+            ),
+            Err(Error::Rejected(
+                Revert::NotEnoughTokensLeft,
+                "only 0.000000000000000005eth left to collect, \
+                 asked for 0.000000000000000010eth"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                10.into(),
+                10.into(),
+                5.into(),
+                10.into(),
+                &plan
+            ),
+            Err(Error::Rejected(
+                Revert::NotEnoughWeth,
+                "not enough WETH: have 0.000000000000000005eth, need 0.000000000000000010eth"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                10.into(),
+                10.into(),
+                10.into(),
+                5.into(),
+                &plan
+            ),
+            Err(Error::Rejected(
+                Revert::NotAllowedToSpendWeth,
+                "ICO is allowed to spend only 0.000000000000000005eth of WETH, \
+                 need 0.000000000000000010eth"
+                    .to_string()
+            ))
+        );
     }
 
-    println!("ICO is finished");
+    #[test]
+    fn plan_is_taken_into_account() {
+        let plan = FundPlan {
+            wrap: Some(5.into()),
+            approve: Some(10.into()),
+        };
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                10.into(),
+                10.into(),
+                5.into(),
+                0.into(),
+                &plan
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check_fund(
+                IcoState::Ongoing,
+                10.into(),
+                10.into(),
+                3.into(),
+                0.into(),
+                &plan
+            ),
+            Err(Error::Rejected(
+                Revert::NotEnoughWeth,
+                "not enough WETH: have 0.000000000000000008eth, need 0.000000000000000010eth \
+                 (after wrapping 0.000000000000000005eth)"
+                    .to_string()
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_quote {
+    use super::*;
+
+    #[test]
+    fn quote_fits() {
+        assert_eq!(
+            quote(IcoState::Ongoing, 10.into(), 4.into()),
+            Quote {
+                eth: 4.into(),
+                spend: 4.into(),
+                fits: true,
+                closes: false,
+            }
+        );
+    }
+
+    #[test]
+    fn quote_closes() {
+        assert_eq!(
+            quote(IcoState::Ongoing, 10.into(), 10.into()),
+            Quote {
+                eth: 10.into(),
+                spend: 10.into(),
+                fits: true,
+                closes: true,
+            }
+        );
+        assert_eq!(
+            quote(IcoState::Ongoing, 10.into(), 15.into()),
+            Quote {
+                eth: 15.into(),
+                spend: 10.into(),
+                fits: false,
+                closes: true,
+            }
+        );
+    }
+
+    #[test]
+    fn quote_closed_ico() {
+        assert_eq!(
+            quote(IcoState::Closed, 0.into(), 4.into()),
+            Quote {
+                eth: 4.into(),
+                spend: 0.into(),
+                fits: false,
+                closes: false,
+            }
+        );
+        assert!(!quote(IcoState::Ongoing, 0.into(), 0.into()).closes);
+    }
+
+    #[test]
+    fn eth_for_scm_rounds_up() {
+        assert_eq!(eth_for_scm(100.into(), 10.into()), Some(10.into()));
+        assert_eq!(eth_for_scm(101.into(), 10.into()), Some(11.into()));
+        assert_eq!(eth_for_scm(0.into(), 10.into()), Some(0.into()));
+        assert_eq!(eth_for_scm(100.into(), 0.into()), None);
+    }
+}
+
+#[cfg(test)]
+mod test_claim_status {
+    use super::*;
+
+    fn snapshot(state: IcoState, finish_time: Option<u64>, timestamp: u64) -> IcoSnapshot {
+        IcoSnapshot {
+            block_number: 1.into(),
+            timestamp,
+            state,
+            left_eth: 0.into(),
+            left_scm: 0.into(),
+            target: 10.into(),
+            rate: 10.into(),
+            hold_duration: 120.into(),
+            close_time: finish_time.map(|time| time.saturating_sub(120)),
+            finish_time,
+            scm: Address::zero(),
+            weth: Address::zero(),
+        }
+    }
+
+    #[test]
+    fn nothing_to_claim() {
+        let finished = snapshot(IcoState::Finished, Some(100), 200);
+        assert_eq!(
+            claim_status(&finished, IcoState::Finished, 0.into()),
+            ClaimStatus::NothingToClaim
+        );
+
+        let ongoing = snapshot(IcoState::Ongoing, None, 200);
+        assert_eq!(
+            claim_status(&ongoing, IcoState::Ongoing, 0.into()),
+            ClaimStatus::NothingToClaim
+        );
+    }
+
+    #[test]
+    fn not_finished() {
+        let ongoing = snapshot(IcoState::Ongoing, None, 200);
+        assert_eq!(
+            claim_status(&ongoing, IcoState::Ongoing, 5.into()),
+            ClaimStatus::Ongoing
+        );
+
+        let closed = snapshot(IcoState::Closed, Some(272), 200);
+        assert_eq!(
+            claim_status(&closed, IcoState::Closed, 5.into()),
+            ClaimStatus::ClaimableIn(72)
+        );
+    }
+
+    #[test]
+    fn claimable() {
+        let finished = snapshot(IcoState::Finished, Some(100), 200);
+        assert_eq!(
+            claim_status(&finished, IcoState::Finished, 5.into()),
+            ClaimStatus::Claimable
+        );
+    }
+
+    #[test]
+    fn finished_in_pending_block() {
+        let closed = snapshot(IcoState::Closed, Some(272), 200);
+        assert_eq!(
+            claim_status(&closed, IcoState::Finished, 5.into()),
+            ClaimStatus::Claimable
+        );
+        assert_eq!(
+            claim_status(&closed, IcoState::Finished, 0.into()),
+            ClaimStatus::NothingToClaim
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_wait_options {
+    use super::*;
+    use structopt::StructOpt;
+
+    #[test]
+    fn poll_interval_is_positive() {
+        assert_eq!(parse_poll_interval("1m"), Ok(Duration(60)));
+        assert!(parse_poll_interval("0").is_err());
+        assert!(parse_poll_interval("0s").is_err());
+    }
+
+    #[test]
+    fn defaults() {
+        let options = WaitOptions::from_iter(&["wait"]);
+        assert!(!options.is_given());
+        assert_eq!(options.poll_interval(), std::time::Duration::from_secs(5));
+        assert_eq!(options.confirmations(), 0);
+
+        let options = WaitOptions::from_iter(&["wait", "--confirmations", "3"]);
+        assert!(options.is_given());
+        assert_eq!(options.confirmations(), 3);
+    }
+}
+
+#[cfg(test)]
+mod test_wait_progress {
+    use super::*;
+
+    #[test]
+    fn wait_progress_to_str() {
+        assert_eq!(
+            WaitProgress::Starting.to_string(),
+            "ICO state is not known yet"
+        );
+        assert_eq!(
+            WaitProgress::Closing {
+                left_eth: U256::exp10(18) / 2
+            }
+            .to_string(),
+            "ICO is ongoing, 0.500000000000000000eth left to collect"
+        );
+        assert_eq!(
+            WaitProgress::Confirming {
+                confirmations: 1,
+                required: 3
+            }
+            .to_string(),
+            "ICO is closed, waiting for confirmations (1/3)"
+        );
+        assert_eq!(
+            WaitProgress::Finishing { seconds_left: 72 }.to_string(),
+            "ICO is closed, finishes in 1m 12s"
+        );
+        assert_eq!(
+            WaitProgress::Finishing { seconds_left: 0 }.to_string(),
+            "ICO is closed and about to finish"
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_close_status {
+    use super::*;
+
+    #[test]
+    fn close_is_confirmed() {
+        let hash = H256::repeat_byte(1);
+        assert_eq!(
+            close_status(10, hash, Some(hash), 10, 0),
+            CloseStatus::Confirmed
+        );
+        assert_eq!(
+            close_status(10, hash, Some(hash), 13, 3),
+            CloseStatus::Confirmed
+        );
+    }
+
+    #[test]
+    fn close_is_confirming() {
+        let hash = H256::repeat_byte(1);
+        assert_eq!(
+            close_status(10, hash, Some(hash), 11, 3),
+            CloseStatus::Confirming { confirmations: 1 }
+        );
+    }
+
+    #[test]
+    fn close_is_orphaned() {
+        let hash = H256::repeat_byte(1);
+        assert_eq!(
+            close_status(10, hash, Some(H256::repeat_byte(2)), 13, 3),
+            CloseStatus::Orphaned
+        );
+        assert_eq!(close_status(10, hash, None, 13, 3), CloseStatus::Orphaned);
+    }
+
+    #[test]
+    fn orphaned_close_is_recovered() {
+        let orphaned = H256::repeat_byte(1);
+        let reincluded = H256::repeat_byte(2);
+        assert_eq!(
+            close_status(10, orphaned, Some(reincluded), 13, 3),
+            CloseStatus::Orphaned
+        );
+
+        // Closing transaction made it into another block at or below
+        // the one we started at, so the block is looked up again.
+        assert_eq!(after_orphan(IcoState::Closed), AfterOrphan::FindCloseBlock);
+        assert_eq!(
+            after_orphan(IcoState::Finished),
+            AfterOrphan::FindCloseBlock
+        );
+        assert_eq!(
+            close_status(9, reincluded, Some(reincluded), 13, 3),
+            CloseStatus::Confirmed
+        );
+
+        // Closing transaction is gone, ICO takes contributions again.
+        assert_eq!(after_orphan(IcoState::Ongoing), AfterOrphan::WaitForClose);
+    }
+}
+
+#[cfg(test)]
+mod test_progress_bar {
+    use super::*;
+
+    #[test]
+    fn progress_bar_to_str() {
+        assert_eq!(
+            progress_bar(0.into(), 10.into()),
+            format!("[{}] 0%", "-".repeat(30))
+        );
+        assert_eq!(
+            progress_bar(5.into(), 10.into()),
+            format!("[{}{}] 50%", "#".repeat(15), "-".repeat(15))
+        );
+        assert_eq!(
+            progress_bar(1.into(), 3.into()),
+            format!("[{}{}] 33%", "#".repeat(9), "-".repeat(21))
+        );
+        assert_eq!(
+            progress_bar(10.into(), 10.into()),
+            format!("[{}] 100%", "#".repeat(30))
+        );
+    }
+
+    #[test]
+    fn progress_bar_is_capped() {
+        assert_eq!(
+            progress_bar(20.into(), 10.into()),
+            format!("[{}] 100%", "#".repeat(30))
+        );
+        assert_eq!(
+            progress_bar(0.into(), 0.into()),
+            format!("[{}] 100%", "#".repeat(30))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_fund {
+    use super::*;
+    use ethcontract::common::abi::{self, Token as AbiToken};
+    use ethcontract::json::json;
+
+    fn fund_log(ico: Address, buyer: Address, eth_used: u64, scm_purchased: u64) -> Log {
+        let signature = crate::contracts::ICO::artifact()
+            .abi
+            .event("Fund")
+            .unwrap()
+            .signature();
+        let data = abi::encode(&[
+            AbiToken::Uint(eth_used.into()),
+            AbiToken::Uint(scm_purchased.into()),
+        ]);
+
+        ethcontract::json::from_value(json!({
+            "address": ico,
+            "topics": [signature, H256::from(buyer)],
+            "data": ethcontract::web3::types::Bytes(data),
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionHash": null,
+            "transactionIndex": null,
+            "logIndex": null,
+            "transactionLogIndex": null,
+            "logType": null,
+            "removed": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn fund_event_is_found() {
+        let ico = Address::repeat_byte(1);
+        let buyer = Address::repeat_byte(2);
+
+        let logs = [fund_log(ico, buyer, 5, 50)];
+        let fund = find_fund_event(&logs, ico, buyer).unwrap();
+        assert_eq!(fund.eth_used, 5.into());
+        assert_eq!(fund.scm_purchased, 50.into());
+    }
+
+    #[test]
+    fn fund_event_is_filtered() {
+        let ico = Address::repeat_byte(1);
+        let buyer = Address::repeat_byte(2);
+        let other = Address::repeat_byte(3);
+
+        let logs = [fund_log(other, buyer, 5, 50), fund_log(ico, other, 5, 50)];
+        assert!(find_fund_event(&logs, ico, buyer).is_none());
+    }
 }
@@ -0,0 +1,283 @@
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use eth_keystore::{
+    CipherparamsJson, CryptoJson, EthKeystore, KdfType, KdfparamsType, KeystoreError,
+};
+use ethcontract::json::{self, Value};
+use ethcontract::prelude::*;
+use ethcontract::web3::signing::keccak256;
+use rand::RngCore;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Ask for the keystore password on the terminal and decrypt the keystore.
+pub fn unlock(path: &Path) -> Result<PrivateKey> {
+    let prompt = format!("Password for {}: ", path.display());
+    let password = rpassword::read_password_from_tty(Some(&prompt))
+        .map_err(|err| Error::Config(format!("can't read keystore password: {}", err)))?;
+
+    decrypt(path, &password)
+}
+
+/// Decrypt a V3 keystore, as written by geth, hardhat or MetaMask.
+///
+/// Both scrypt and pbkdf2 key derivation functions are supported.
+pub fn decrypt(path: &Path, password: &str) -> Result<PrivateKey> {
+    let key = eth_keystore::decrypt_key(path, password).map_err(|err| match err {
+        KeystoreError::MacMismatch => {
+            Error::Input(format!("wrong password for keystore {}", path.display()))
+        }
+        err => Error::Config(format!("can't read keystore {}: {}", path.display(), err)),
+    })?;
+
+    PrivateKey::from_slice(key).map_err(|err| {
+        Error::Config(format!(
+            "keystore {} contains invalid private key: {}",
+            path.display(),
+            err
+        ))
+    })
+}
+
+/// Parameters of scrypt for new keystores, same as geth's standard ones
+/// (N = 2^18, r = 8, p = 1).
+const SCRYPT_LOG_N: u8 = 18;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Encrypt a private key with scrypt and write it to `path` as a V3 keystore.
+///
+/// Like geth, we store the address next to the encrypted key, so that
+/// accounts can be listed without asking for passwords. Fails if `path`
+/// already exists. On unix, the file is only readable by its owner
+/// from the moment it's created.
+pub fn encrypt(path: &Path, key: &PrivateKey, password: &str) -> Result<()> {
+    let failed = |err: &dyn std::fmt::Display| {
+        Error::Config(format!("can't write keystore {}: {}", path.display(), err))
+    };
+
+    let keystore = encrypt_to_json(key, password).map_err(|err| failed(&err))?;
+
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt as _;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path).map_err(|err| match err.kind() {
+        std::io::ErrorKind::AlreadyExists => {
+            Error::Input(format!("keystore {} already exists", path.display()))
+        }
+        _ => failed(&err),
+    })?;
+
+    file.write_all(keystore.to_string().as_bytes())
+        .map_err(|err| failed(&err))
+}
+
+/// Build a V3 keystore with the private key encrypted by aes-128-ctr under
+/// a key derived from the password with scrypt.
+///
+/// This is what `eth_keystore::encrypt_key` does, but that function also
+/// writes the file itself: with default permissions, overwriting whatever is
+/// there, and without the address. Building the JSON here lets [`encrypt`]
+/// create the file owner-only and exclusively, in a single write. It also
+/// lets us pick scrypt parameters, as `encrypt_key` hardcodes N = 2^13.
+fn encrypt_to_json(key: &PrivateKey, password: &str) -> Result<Value, String> {
+    let mut rng = rand::thread_rng();
+
+    let mut salt = vec![0; 32];
+    rng.fill_bytes(&mut salt);
+    let mut iv = vec![0; 16];
+    rng.fill_bytes(&mut iv);
+
+    let mut derived = [0; 32];
+    let params =
+        scrypt::Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P).map_err(|err| err.to_string())?;
+    scrypt::scrypt(password.as_bytes(), &salt, &params, &mut derived)
+        .map_err(|err| err.to_string())?;
+
+    let mut ciphertext = key[..].to_vec();
+    ctr::Ctr128BE::<aes::Aes128>::new(derived[..16].into(), iv[..].into())
+        .apply_keystream(&mut ciphertext);
+
+    let mac = keccak256(&[&derived[16..], &ciphertext[..]].concat());
+
+    let keystore = EthKeystore {
+        id: uuid::Uuid::new_v4(),
+        version: 3,
+        crypto: CryptoJson {
+            cipher: "aes-128-ctr".to_string(),
+            cipherparams: CipherparamsJson { iv },
+            ciphertext,
+            kdf: KdfType::Scrypt,
+            kdfparams: KdfparamsType::Scrypt {
+                dklen: derived.len() as u8,
+                n: 1 << SCRYPT_LOG_N,
+                p: SCRYPT_P,
+                r: SCRYPT_R,
+                salt,
+            },
+            mac: mac.to_vec(),
+        },
+    };
+
+    let mut keystore = json::to_value(&keystore).map_err(|err| err.to_string())?;
+    let address = format!("{:x}", key.public_address());
+    keystore["address"] = address.trim_start_matches("0x").into();
+
+    Ok(keystore)
+}
+
+/// Read the address stored in a keystore, without decrypting it.
+///
+/// The address is optional in V3 keystores, so this returns `None`
+/// if the keystore doesn't have it.
+pub fn read_address(path: &Path) -> Result<Option<Address>> {
+    let invalid = |err: &dyn std::fmt::Display| {
+        Error::Config(format!("can't read keystore {}: {}", path.display(), err))
+    };
+
+    let contents = std::fs::read_to_string(path).map_err(|err| invalid(&err))?;
+    let keystore: Value = json::from_str(&contents).map_err(|err| invalid(&err))?;
+
+    match keystore.get("address").and_then(|address| address.as_str()) {
+        Some(address) => address
+            .trim_start_matches("0x")
+            .parse()
+            .map(Some)
+            .map_err(|err| invalid(&err)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test_keystore {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Test vector from the Web3 Secret Storage definition.
+    const PBKDF2_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {"iv": "6087dab2f9fdbbfaddc31a909735c1e6"},
+            "ciphertext": "5318b4d5bcd28de64ee5559e671353e16f075ecae9f99c7a79a38af5f869aa46",
+            "kdf": "pbkdf2",
+            "kdfparams": {
+                "c": 262144,
+                "dklen": 32,
+                "prf": "hmac-sha256",
+                "salt": "ae3cd4e7013836a3df6bd7241b12db061dbe2c6785853cce422d148a624ce0bd"
+            },
+            "mac": "517ead924a9d0dc3124507e3393d175ce3ff7c1e96529c6c555ce9e51205e9b2"
+        },
+        "id": "3198bc9c-6672-5ab3-d995-4942343ae5b6",
+        "version": 3
+    }"#;
+
+    const PBKDF2_KEY: &str = "7a28b5ba57c53603b0b07b56bba752f7784bf506fa95edc395f5cf6c7514fe9d";
+
+    /// Same key and password, encrypted with cheap scrypt parameters
+    /// so that the test doesn't take forever in debug builds.
+    const SCRYPT_KEYSTORE: &str = r#"{
+        "crypto": {
+            "cipher": "aes-128-ctr",
+            "cipherparams": {"iv": "7eb12e1f96433dfe83747bdd9d5ab9d3"},
+            "ciphertext": "2f95a41c3dc48cea2f965909787e2ab6419509f070913dd0ec4cad4377d6a732",
+            "kdf": "scrypt",
+            "kdfparams": {
+                "dklen": 32,
+                "n": 8192,
+                "p": 1,
+                "r": 8,
+                "salt": "473f94af19ab029f3aeaade8b1c33743d51a8f0d44a7bc05d2cdedfa19a80748"
+            },
+            "mac": "6796464dd9a3606f8c87c30bc76fbffcae321793f34b54e5485270176f13ba6e"
+        },
+        "id": "9d0552dd-1a85-4cd5-8824-60ae4b849be7",
+        "version": 3
+    }"#;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("fake-ico-test-{}-{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn decrypt_pbkdf2() {
+        let path = temp_dir("pbkdf2").join("keystore.json");
+        std::fs::write(&path, PBKDF2_KEYSTORE).unwrap();
+
+        let key = decrypt(&path, "testpassword").unwrap();
+        let expected = PrivateKey::from_hex_str(PBKDF2_KEY).unwrap();
+        assert_eq!(key.public_address(), expected.public_address());
+
+        assert!(matches!(decrypt(&path, "wrong"), Err(Error::Input(_))));
+    }
+
+    #[test]
+    fn decrypt_scrypt() {
+        let path = temp_dir("scrypt").join("keystore.json");
+        std::fs::write(&path, SCRYPT_KEYSTORE).unwrap();
+
+        let key = decrypt(&path, "testpassword").unwrap();
+        let expected = PrivateKey::from_hex_str(PBKDF2_KEY).unwrap();
+        assert_eq!(key.public_address(), expected.public_address());
+    }
+
+    #[test]
+    fn encrypt_and_decrypt() {
+        let path = temp_dir("encrypt").join("account.json");
+        let expected = PrivateKey::from_hex_str(PBKDF2_KEY).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        encrypt(&path, &expected, "testpassword").unwrap();
+
+        assert_eq!(
+            read_address(&path).unwrap(),
+            Some(expected.public_address())
+        );
+        assert_eq!(
+            decrypt(&path, "testpassword").unwrap().public_address(),
+            expected.public_address()
+        );
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[test]
+    fn encrypt_does_not_overwrite() {
+        let path = temp_dir("overwrite").join("account.json");
+        std::fs::write(&path, PBKDF2_KEYSTORE).unwrap();
+
+        let key = PrivateKey::from_hex_str(PBKDF2_KEY).unwrap();
+        assert!(matches!(
+            encrypt(&path, &key, "testpassword"),
+            Err(Error::Input(_))
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), PBKDF2_KEYSTORE);
+    }
+
+    #[test]
+    fn address_is_optional() {
+        let path = temp_dir("address").join("keystore.json");
+        std::fs::write(&path, PBKDF2_KEYSTORE).unwrap();
+        assert_eq!(read_address(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn missing_keystore() {
+        let path = temp_dir("missing").join("keystore.json");
+        assert!(matches!(decrypt(&path, ""), Err(Error::Config(_))));
+    }
+}
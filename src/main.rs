@@ -1,11 +1,24 @@
+use structopt::clap::ErrorKind;
 use structopt::StructOpt;
 
 use ethcontract::prelude::*;
 
+use crate::error::{Error, Result};
+use crate::signer::SigningTransport;
+
+mod account;
+mod audit;
 mod cli;
 mod contracts;
+mod contributors;
 mod erc20;
+mod error;
 mod ico;
+mod keystore;
+mod mnemonic;
+mod revert;
+mod signer;
+mod snapshot;
 
 #[derive(StructOpt)]
 #[structopt(about = "Use CLI to spend your precious ETH and get some 💩")]
@@ -18,6 +31,15 @@ struct Opts {
     )]
     transport: String,
 
+    #[structopt(
+        long,
+        help = "refuse to run unless the node is on this chain, i.e. 31337 for hardhat"
+    )]
+    chain_id: Option<u64>,
+
+    #[structopt(flatten)]
+    signer: signer::SignerOpts,
+
     #[structopt(subcommand)]
     subcommand: SubCommand,
 }
@@ -26,39 +48,108 @@ struct Opts {
 enum SubCommand {
     Scm(erc20::ScmCommand),
     Weth(erc20::WethCommand),
+    Token(erc20::TokenCommand),
     Ico(ico::IcoCommand),
+    Account(account::AccountCommand),
 }
 
 #[tokio::main]
 async fn main() {
-    let opts = Opts::from_args();
+    let result = match parse_opts() {
+        Ok(opts) => run(opts).await,
+        Err(err) => Err(err),
+    };
 
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        std::process::exit(err.exit_code());
+    }
+}
+
+/// Parse command line arguments.
+///
+/// Malformed values, like `1.5wie` given as an amount, are input errors
+/// and exit with the same code no matter whether they're caught by clap
+/// or by the command itself. Everything else, including `--help`,
+/// is handled by clap as usual.
+fn parse_opts() -> Result<Opts> {
+    Opts::from_iter_safe(std::env::args_os()).map_err(|err| match err.kind {
+        ErrorKind::ValueValidation | ErrorKind::InvalidValue => {
+            Error::Input(err.message.trim_end().to_string())
+        }
+        _ => err.exit(),
+    })
+}
+
+async fn run(opts: Opts) -> Result<()> {
+    // Managing accounts needs neither a node nor an account.
+    if let SubCommand::Account(account) = &opts.subcommand {
+        let dir = account::keystore_dir(opts.signer.keystore_dir.as_deref())?;
+        return account.invoke(&dir);
+    }
+
+    let signer_kind = signer::choose(&opts.signer)?;
     let url = std::env::var("ETH_TRANSPORT").unwrap_or(opts.transport);
 
-    let account = get_account();
-    let transport = Http::new(&url).expect("http(s) connection failed");
-    let web3 = Web3::new(transport);
+    let node = Http::new(&url)
+        .map_err(|err| Error::Config(format!("invalid node url {}: {}", url, err)))?;
+
+    let chain_id = get_chain_id(&Web3::new(node.clone()), opts.chain_id).await?;
+    let signer = signer::from_config(&opts.signer, signer_kind, chain_id).await?;
+    let account = signer.account();
+    let web3 = Web3::new(SigningTransport::new(node, signer.remote().cloned()));
 
     match opts.subcommand {
         SubCommand::Scm(scm) => scm.invoke(account, &web3).await,
         SubCommand::Weth(weth) => weth.invoke(account, &web3).await,
+        SubCommand::Token(token) => token.invoke(account, &web3).await,
         SubCommand::Ico(ico) => ico.invoke(account, &web3).await,
-    };
+        SubCommand::Account(_) => unreachable!("handled above"),
+    }
+}
+
+/// Fetch id of the chain the node is on, so that transactions we sign
+/// can't be replayed on other chains (EIP-155).
+async fn get_chain_id(web3: &Web3<Http>, expected: Option<u64>) -> Result<u64> {
+    let chain_id = web3.eth().chain_id().await?;
+    if chain_id > U256::from(u64::MAX) {
+        return Err(Error::Transport(format!(
+            "node reported invalid chain id {}",
+            chain_id
+        )));
+    }
+
+    check_chain_id(chain_id.as_u64(), expected)
+}
+
+/// Make sure the node is on the chain the user asked for.
+fn check_chain_id(chain_id: u64, expected: Option<u64>) -> Result<u64> {
+    match expected {
+        Some(expected) if expected != chain_id => Err(Error::Config(format!(
+            "node is on chain {}, but --chain-id is {}",
+            chain_id, expected
+        ))),
+        _ => Ok(chain_id),
+    }
 }
 
-fn get_account() -> Account {
-    if let Ok(pk) = std::env::var("ETH_PK") {
-        let pk = PrivateKey::from_hex_str(pk).expect("invalid private key");
-        Account::Offline(pk, None)
-    } else {
-        let address = std::env::var("ETH_ACCOUNT")
-            .expect("environment variable ETH_PK or ETH_ACCOUNT must be present")
-            .parse()
-            .expect("environment variable ETH_ACCOUNT must contain valid address");
+#[cfg(test)]
+mod test_chain_id {
+    use super::*;
 
-        let password = std::env::var("ETH_PASSWORD")
-            .expect("environment variable ETH_PASSWORD must be present");
+    #[test]
+    fn chain_id_matches() {
+        assert_eq!(check_chain_id(31337, None), Ok(31337));
+        assert_eq!(check_chain_id(31337, Some(31337)), Ok(31337));
+    }
 
-        Account::Locked(address, Password::new(password), None)
+    #[test]
+    fn chain_id_mismatch() {
+        assert_eq!(
+            check_chain_id(31337, Some(1)),
+            Err(Error::Config(
+                "node is on chain 31337, but --chain-id is 1".to_string()
+            ))
+        );
     }
 }
@@ -0,0 +1,197 @@
+use bip39::{Language, Mnemonic, Seed};
+use ethcontract::prelude::*;
+use hmac::{Hmac, Mac};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use sha2::Sha512;
+
+use crate::error::{Error, Result};
+
+/// Child indices starting from this one derive hardened keys.
+const HARDENED: u32 = 1 << 31;
+
+/// Derivation path for the account with the given index, same as the one
+/// hardhat, MetaMask and most other wallets use.
+pub fn default_derivation_path(index: u32) -> String {
+    format!("m/44'/60'/0'/0/{}", index)
+}
+
+/// Derive a private key from a BIP-39 mnemonic using a BIP-32 derivation path.
+pub fn derive_key(phrase: &str, path: &str) -> Result<PrivateKey> {
+    let mnemonic = Mnemonic::from_phrase(phrase, Language::English)
+        .map_err(|err| Error::Config(format!("invalid mnemonic: {}", err)))?;
+    let path = parse_derivation_path(path)
+        .ok_or_else(|| Error::Config(format!("invalid derivation path {}", path)))?;
+
+    let seed = Seed::new(&mnemonic, "");
+    let key = derive_secret(seed.as_bytes(), &path).ok_or_else(|| {
+        Error::Config("mnemonic gives invalid private key at this path".to_string())
+    })?;
+
+    PrivateKey::from_slice(&key[..])
+        .map_err(|err| Error::Config(format!("mnemonic gives invalid private key: {}", err)))
+}
+
+/// Parse a path like `m/44'/60'/0'/0/0` into child indices.
+///
+/// Hardened indices are marked with `'` or `h`.
+fn parse_derivation_path(path: &str) -> Option<Vec<u32>> {
+    let mut parts = path.split('/');
+    if parts.next() != Some("m") {
+        return None;
+    }
+
+    parts
+        .map(|part| {
+            let (number, hardened) = match part.strip_suffix(|c| c == '\'' || c == 'h') {
+                Some(number) => (number, HARDENED),
+                None => (part, 0),
+            };
+
+            if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+
+            number
+                .parse::<u32>()
+                .ok()
+                .filter(|index| *index < HARDENED)
+                .map(|index| index | hardened)
+        })
+        .collect()
+}
+
+/// BIP-32 private key derivation from a seed.
+///
+/// Returns `None` if some key along the path is invalid, which
+/// happens with probability lower than 1 in 2^127.
+fn derive_secret(seed: &[u8], path: &[u32]) -> Option<SecretKey> {
+    let (mut key, mut chain_code) = split_hmac(b"Bitcoin seed", seed)?;
+    let secp = Secp256k1::signing_only();
+
+    for &index in path {
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED {
+            data.push(0);
+            data.extend_from_slice(&key[..]);
+        } else {
+            data.extend_from_slice(&PublicKey::from_secret_key(&secp, &key).serialize());
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let (tweak, child_chain_code) = split_hmac(&chain_code, &data)?;
+        key.add_assign(&tweak[..]).ok()?;
+        chain_code = child_chain_code;
+    }
+
+    Some(key)
+}
+
+/// Compute HMAC-SHA512 of the data and split it into a secret key
+/// and a chain code.
+fn split_hmac(hmac_key: &[u8], data: &[u8]) -> Option<(SecretKey, [u8; 32])> {
+    let mut mac = Hmac::<Sha512>::new_from_slice(hmac_key).ok()?;
+    mac.update(data);
+    let output = mac.finalize().into_bytes();
+
+    let key = SecretKey::from_slice(&output[..32]).ok()?;
+    let mut chain_code = [0; 32];
+    chain_code.copy_from_slice(&output[32..]);
+
+    Some((key, chain_code))
+}
+
+#[cfg(test)]
+mod test_mnemonic {
+    use super::*;
+
+    /// Mnemonic hardhat uses for its built-in network.
+    const HARDHAT_MNEMONIC: &str = "test test test test test test test test test test test junk";
+
+    /// Mnemonic from `hardhat.config.ts`.
+    const DEFAULT_MNEMONIC: &str =
+        "candy maple cake sugar pudding cream honey rich smooth crumble sweet treat";
+
+    fn address(key: &PrivateKey) -> String {
+        format!("{:?}", key.public_address())
+    }
+
+    #[test]
+    fn hardhat_accounts() {
+        let key = derive_key(HARDHAT_MNEMONIC, &default_derivation_path(0)).unwrap();
+        assert_eq!(
+            key.public_address(),
+            PrivateKey::from_hex_str(
+                "ac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80"
+            )
+            .unwrap()
+            .public_address()
+        );
+        assert_eq!(address(&key), "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266");
+
+        let key = derive_key(HARDHAT_MNEMONIC, &default_derivation_path(1)).unwrap();
+        assert_eq!(address(&key), "0x70997970c51812dc3a010c7d01b50e0d17dc79c8");
+    }
+
+    #[test]
+    fn default_mnemonic_accounts() {
+        let key = derive_key(DEFAULT_MNEMONIC, &default_derivation_path(0)).unwrap();
+        assert_eq!(address(&key), "0x627306090abab3a6e1400e9345bc60c78a8bef57");
+    }
+
+    #[test]
+    fn custom_derivation_path() {
+        let default = derive_key(HARDHAT_MNEMONIC, &default_derivation_path(0)).unwrap();
+        let custom = derive_key(HARDHAT_MNEMONIC, "m/44'/60'/1'/0/0").unwrap();
+        assert_ne!(custom.public_address(), default.public_address());
+    }
+
+    #[test]
+    fn bip32_test_vector() {
+        let seed: Vec<u8> = (0..16).collect();
+        let derive = |path| derive_secret(&seed, &parse_derivation_path(path).unwrap());
+        let key = |hex: &str| Some(hex.parse::<SecretKey>().unwrap());
+
+        assert_eq!(
+            derive("m/0'"),
+            key("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+        );
+        assert_eq!(
+            derive("m/0'/1"),
+            key("3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368")
+        );
+        assert_eq!(
+            derive("m/0h/1/2h/2/1000000000"),
+            key("471b76e389e528d6de6d816857e012c5455051cad6660850e58372a6c3e6e7c8")
+        );
+    }
+
+    #[test]
+    fn derivation_paths() {
+        assert_eq!(parse_derivation_path("m"), Some(vec![]));
+        assert_eq!(
+            parse_derivation_path("m/44'/60h/0"),
+            Some(vec![44 | HARDENED, 60 | HARDENED, 0])
+        );
+        assert_eq!(parse_derivation_path("44'/60'"), None);
+        assert_eq!(parse_derivation_path("m/"), None);
+        assert_eq!(parse_derivation_path("m/+1"), None);
+        assert_eq!(parse_derivation_path("m/2147483648"), None);
+    }
+
+    #[test]
+    fn invalid_mnemonic() {
+        let path = default_derivation_path(0);
+        assert!(matches!(
+            derive_key("test test test", &path),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(
+            derive_key(&HARDHAT_MNEMONIC.replace("junk", "test"), &path),
+            Err(Error::Config(_))
+        ));
+        assert!(matches!(
+            derive_key(HARDHAT_MNEMONIC, "44'/60'/0'/0/0"),
+            Err(Error::Config(_))
+        ));
+    }
+}
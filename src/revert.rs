@@ -0,0 +1,299 @@
+use ethcontract::common::abi::{self, ParamType, Token as AbiToken};
+use ethcontract::errors::{ExecutionError, MethodError};
+use ethcontract::jsonrpc::Error as RpcError;
+use ethcontract::web3::types::Bytes;
+use ethcontract::web3::Error as Web3Error;
+use std::fmt::{Display, Formatter};
+
+/// Selector of the `Error(string)` function, used to encode revert reasons.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// Reason why a contract rejected our transaction.
+///
+/// Known reasons come from `require` statements in `ico.sol` and `scm.sol`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Revert {
+    /// ICO is closed and doesn't accept contributions anymore.
+    IcoClosed,
+
+    /// Contribution is bigger than the number of ETH left to collect.
+    NotEnoughTokensLeft,
+
+    /// Contributor's WETH balance is less than the contribution.
+    NotEnoughWeth,
+
+    /// ICO is not allowed to spend enough of contributor's WETH.
+    NotAllowedToSpendWeth,
+
+    /// ICO is ongoing, so close and finish times are not known yet.
+    IcoStillOngoing,
+
+    /// ICO is not finished, so tokens can't be claimed yet.
+    IcoNotFinished,
+
+    /// User has no SCM tokens to claim.
+    NothingToClaim,
+
+    /// SCM transfer from the zero address.
+    SendingFromZeroAddress,
+
+    /// SCM transfer to the zero address.
+    SendingToZeroAddress,
+
+    /// Token owner doesn't have enough tokens.
+    NotEnoughFunds,
+
+    /// Spender is not allowed to transfer this many tokens.
+    AllowanceExhausted,
+
+    /// Approving the zero address to spend tokens.
+    ApprovingZeroAddress,
+
+    /// Contract reverted with a reason we don't know about.
+    Other(String),
+
+    /// Contract reverted without giving any reason.
+    Unknown,
+}
+
+impl Revert {
+    /// Convert a revert reason string to a typed value.
+    pub fn from_reason(reason: Option<&str>) -> Self {
+        match reason {
+            Some("ICO is closed") => Revert::IcoClosed,
+            Some("not enough tokens left") => Revert::NotEnoughTokensLeft,
+            Some("not enough WETH") => Revert::NotEnoughWeth,
+            Some("not allowed to spend WETH") => Revert::NotAllowedToSpendWeth,
+            Some("ICO is still ongoing") => Revert::IcoStillOngoing,
+            Some("ICO is not finished yet") => Revert::IcoNotFinished,
+            Some("no SCM tokens to claim") => Revert::NothingToClaim,
+            Some("sending from zero address") => Revert::SendingFromZeroAddress,
+            Some("sending to zero address") => Revert::SendingToZeroAddress,
+            Some("not enough funds") => Revert::NotEnoughFunds,
+            Some("allowance exhausted") => Revert::AllowanceExhausted,
+            Some("setting allowance for zero address") => Revert::ApprovingZeroAddress,
+            Some(reason) => Revert::Other(reason.to_string()),
+            None => Revert::Unknown,
+        }
+    }
+
+    /// Extract revert reason from a failed contract call.
+    ///
+    /// Different nodes report reverts differently; ethcontract understands
+    /// geth, parity and ganache, and we additionally check for hardhat-style
+    /// errors. Returns `None` if the call failed for some other reason.
+    pub fn from_error(err: &MethodError) -> Option<Self> {
+        Self::from_execution_error(&err.inner)
+    }
+
+    /// Extract revert reason from an execution error, see [`Revert::from_error`].
+    pub fn from_execution_error(err: &ExecutionError) -> Option<Self> {
+        match err {
+            ExecutionError::Revert(reason) => Some(Revert::from_reason(reason.as_deref())),
+            ExecutionError::Failure(_) => Some(Revert::Unknown),
+            ExecutionError::Web3(Web3Error::Rpc(err)) => decode_rpc_error(err),
+            _ => None,
+        }
+    }
+
+    /// Exit code the CLI uses when a transaction is reverted for this reason.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Revert::Unknown => 10,
+            Revert::Other(_) => 11,
+            Revert::IcoClosed => 12,
+            Revert::NotEnoughTokensLeft => 13,
+            Revert::NotEnoughWeth => 14,
+            Revert::NotAllowedToSpendWeth => 15,
+            Revert::IcoStillOngoing => 16,
+            Revert::IcoNotFinished => 17,
+            Revert::NothingToClaim => 18,
+            Revert::SendingFromZeroAddress => 19,
+            Revert::SendingToZeroAddress => 20,
+            Revert::NotEnoughFunds => 21,
+            Revert::AllowanceExhausted => 22,
+            Revert::ApprovingZeroAddress => 23,
+        }
+    }
+}
+
+impl Display for Revert {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Revert::IcoClosed => f.write_str("ICO is closed"),
+            Revert::NotEnoughTokensLeft => f.write_str("not enough tokens left"),
+            Revert::NotEnoughWeth => f.write_str("not enough WETH"),
+            Revert::NotAllowedToSpendWeth => f.write_str("ICO is not allowed to spend WETH"),
+            Revert::IcoStillOngoing => f.write_str("ICO is still ongoing"),
+            Revert::IcoNotFinished => f.write_str("ICO is not finished yet"),
+            Revert::NothingToClaim => f.write_str("no SCM tokens to claim"),
+            Revert::SendingFromZeroAddress => f.write_str("sending from zero address"),
+            Revert::SendingToZeroAddress => f.write_str("sending to zero address"),
+            Revert::NotEnoughFunds => f.write_str("not enough funds"),
+            Revert::AllowanceExhausted => f.write_str("allowance exhausted"),
+            Revert::ApprovingZeroAddress => f.write_str("setting allowance for zero address"),
+            Revert::Other(reason) => f.write_str(reason),
+            Revert::Unknown => f.write_str("no reason given"),
+        }
+    }
+}
+
+/// Look for a revert reason in a JSON RPC error.
+///
+/// Hardhat puts ABI-encoded revert data into `error.data.data`, geth puts it
+/// into `error.data`; the reason can also be given as a part of the message.
+fn decode_rpc_error(err: &RpcError) -> Option<Revert> {
+    let data = err.data.as_ref().and_then(|data| match data {
+        ethcontract::json::Value::Object(object) => object.get("data"),
+        data => Some(data),
+    });
+
+    if let Some(reason) = data.and_then(decode_reason) {
+        return Some(Revert::from_reason(Some(&reason)));
+    }
+
+    const REASON_STRING: &str = "reverted with reason string '";
+    if let Some(start) = err.message.find(REASON_STRING) {
+        let reason = &err.message[start + REASON_STRING.len()..];
+        let reason = reason.strip_suffix('\'').unwrap_or(reason);
+        return Some(Revert::from_reason(Some(reason)));
+    }
+
+    if err.message.contains("reverted without a reason")
+        || err.message.contains("execution reverted")
+    {
+        return Some(Revert::Unknown);
+    }
+
+    None
+}
+
+/// Decode ABI-encoded `Error(string)`.
+fn decode_reason(data: &ethcontract::json::Value) -> Option<String> {
+    let bytes: Bytes = ethcontract::json::from_value(data.clone()).ok()?;
+    let payload = bytes.0.strip_prefix(&ERROR_SELECTOR[..])?;
+
+    match abi::decode(&[ParamType::String], payload).ok()?.pop()? {
+        AbiToken::String(reason) => Some(reason),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_revert {
+    use super::*;
+    use ethcontract::json::json;
+
+    fn encode_reason(reason: &str) -> String {
+        let mut bytes = ERROR_SELECTOR.to_vec();
+        bytes.extend(abi::encode(&[AbiToken::String(reason.to_string())]));
+        ethcontract::json::to_value(Bytes(bytes))
+            .unwrap()
+            .as_str()
+            .unwrap()
+            .to_string()
+    }
+
+    fn method_error(inner: ExecutionError) -> MethodError {
+        MethodError::from_parts("fund(uint256)".to_string(), inner)
+    }
+
+    fn rpc_error(message: &str, data: Option<ethcontract::json::Value>) -> MethodError {
+        method_error(ExecutionError::Web3(Web3Error::Rpc(RpcError {
+            code: (-32603).into(),
+            message: message.to_string(),
+            data,
+        })))
+    }
+
+    #[test]
+    fn revert_from_reason() {
+        assert_eq!(
+            Revert::from_reason(Some("ICO is closed")),
+            Revert::IcoClosed
+        );
+        assert_eq!(
+            Revert::from_reason(Some("allowance exhausted")),
+            Revert::AllowanceExhausted
+        );
+        assert_eq!(
+            Revert::from_reason(Some("something else")),
+            Revert::Other("something else".to_string())
+        );
+        assert_eq!(Revert::from_reason(None), Revert::Unknown);
+    }
+
+    #[test]
+    fn revert_from_execution_error() {
+        let err = method_error(ExecutionError::Revert(Some(
+            "no SCM tokens to claim".to_string(),
+        )));
+        assert_eq!(Revert::from_error(&err), Some(Revert::NothingToClaim));
+
+        let err = method_error(ExecutionError::InvalidOpcode);
+        assert_eq!(Revert::from_error(&err), None);
+    }
+
+    #[test]
+    fn revert_from_hardhat_error() {
+        let err = rpc_error(
+            "Error: VM Exception while processing transaction: reverted with reason string 'ICO is closed'",
+            Some(json!({
+                "message": "Error: VM Exception while processing transaction: reverted with reason string 'ICO is closed'",
+                "data": encode_reason("ICO is closed"),
+            })),
+        );
+        assert_eq!(Revert::from_error(&err), Some(Revert::IcoClosed));
+
+        let err = rpc_error(
+            "Error: VM Exception while processing transaction: reverted with reason string 'not enough tokens left'",
+            None,
+        );
+        assert_eq!(Revert::from_error(&err), Some(Revert::NotEnoughTokensLeft));
+
+        let err = rpc_error("Error: Transaction reverted without a reason string", None);
+        assert_eq!(Revert::from_error(&err), Some(Revert::Unknown));
+    }
+
+    #[test]
+    fn revert_from_geth_error() {
+        let err = rpc_error(
+            "execution reverted: ICO is not finished yet",
+            Some(json!(encode_reason("ICO is not finished yet"))),
+        );
+        assert_eq!(Revert::from_error(&err), Some(Revert::IcoNotFinished));
+    }
+
+    #[test]
+    fn unrelated_rpc_error() {
+        let err = rpc_error("nonce too low", None);
+        assert_eq!(Revert::from_error(&err), None);
+    }
+
+    #[test]
+    fn exit_codes_are_distinct() {
+        let reverts = [
+            Revert::IcoClosed,
+            Revert::NotEnoughTokensLeft,
+            Revert::NotEnoughWeth,
+            Revert::NotAllowedToSpendWeth,
+            Revert::IcoStillOngoing,
+            Revert::IcoNotFinished,
+            Revert::NothingToClaim,
+            Revert::SendingFromZeroAddress,
+            Revert::SendingToZeroAddress,
+            Revert::NotEnoughFunds,
+            Revert::AllowanceExhausted,
+            Revert::ApprovingZeroAddress,
+            Revert::Other(String::new()),
+            Revert::Unknown,
+        ];
+
+        let mut codes: Vec<_> = reverts.iter().map(Revert::exit_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+
+        assert_eq!(codes.len(), reverts.len());
+        assert!(codes.iter().all(|&code| code >= 10));
+    }
+}
@@ -0,0 +1,794 @@
+use ethcontract::json::{json, Value};
+use ethcontract::jsonrpc::{Call, Params};
+use ethcontract::prelude::*;
+use ethcontract::web3::{self, BatchTransport, RequestId, Transport};
+use futures::future::{BoxFuture, FutureExt as _};
+use std::fmt::{Debug, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::{Error, Result};
+
+/// Where to get the account from, in addition to environment variables.
+#[derive(structopt::StructOpt)]
+pub struct SignerOpts {
+    #[structopt(
+        long,
+        possible_values = SignerKind::VARIANTS,
+        help = "how to sign transactions, see also ETH_SIGNER; guessed from other options"
+    )]
+    signer: Option<SignerKind>,
+
+    #[structopt(
+        long,
+        help = "sign with a key from this encrypted JSON keystore, the password is asked for"
+    )]
+    keystore: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        conflicts_with = "keystore",
+        help = "sign with a stored account, see `account list`"
+    )]
+    from: Option<String>,
+
+    #[structopt(
+        long,
+        help = "where accounts are stored, ETH_KEYSTORE_DIR or ~/.fake-ico/keystore by default"
+    )]
+    pub keystore_dir: Option<PathBuf>,
+
+    #[structopt(
+        long,
+        help = "sign with a key derived from this BIP-39 mnemonic, see also ETH_MNEMONIC"
+    )]
+    mnemonic: Option<String>,
+
+    #[structopt(
+        long,
+        help = "index of the account derived from the mnemonic [default: 0]"
+    )]
+    account_index: Option<u32>,
+
+    #[structopt(
+        long,
+        conflicts_with = "account-index",
+        help = "derivation path of the account, m/44'/60'/0'/0/<account-index> by default"
+    )]
+    derivation_path: Option<String>,
+
+    #[structopt(
+        long,
+        help = "sign with a remote signer such as Clef, ETH_SIGNER_URL or localhost:8550 by default"
+    )]
+    signer_url: Option<String>,
+}
+
+impl SignerOpts {
+    /// Command line options that were given, with the signer each belongs to.
+    fn given(&self) -> Vec<(SignerKind, &'static str)> {
+        let options = [
+            (self.keystore.is_some(), SignerKind::Keystore, "--keystore"),
+            (self.from.is_some(), SignerKind::Keystore, "--from"),
+            (
+                self.keystore_dir.is_some(),
+                SignerKind::Keystore,
+                "--keystore-dir",
+            ),
+            (self.mnemonic.is_some(), SignerKind::Mnemonic, "--mnemonic"),
+            (
+                self.account_index.is_some(),
+                SignerKind::Mnemonic,
+                "--account-index",
+            ),
+            (
+                self.derivation_path.is_some(),
+                SignerKind::Mnemonic,
+                "--derivation-path",
+            ),
+            (
+                self.signer_url.is_some(),
+                SignerKind::Remote,
+                "--signer-url",
+            ),
+        ];
+
+        options
+            .iter()
+            .filter(|(given, _, _)| *given)
+            .map(|&(_, kind, name)| (kind, name))
+            .collect()
+    }
+
+    /// Make sure no option for some other signer was given, so that
+    /// we never sign with an account the user didn't mean.
+    fn check(&self, kind: SignerKind) -> Result<()> {
+        let foreign: Vec<_> = self
+            .given()
+            .into_iter()
+            .filter(|(owner, _)| *owner != kind)
+            .map(|(_, name)| name)
+            .collect();
+
+        if foreign.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Config(format!(
+                "{} can't be used with the {} signer",
+                foreign.join(", "),
+                kind
+            )))
+        }
+    }
+}
+
+/// Where transactions are signed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SignerKind {
+    /// Private key in `ETH_PK`.
+    Key,
+
+    /// Encrypted JSON keystore, given by its path or account name.
+    Keystore,
+
+    /// Key derived from a BIP-39 mnemonic.
+    Mnemonic,
+
+    /// Account managed by the node, `ETH_ACCOUNT`.
+    Node,
+
+    /// Separate signer that speaks `eth_signTransaction`, i.e. Clef.
+    Remote,
+}
+
+impl SignerKind {
+    pub const VARIANTS: &'static [&'static str] =
+        &["key", "keystore", "mnemonic", "node", "remote"];
+
+    /// Guess the signer from the options and environment variables given.
+    ///
+    /// Command line options take precedence over environment variables,
+    /// so `--account-index` picks the mnemonic even if `ETH_PK` is set;
+    /// `ETH_PK` takes precedence over `ETH_MNEMONIC`, same as `PK` over
+    /// `MNEMONIC` in the hardhat config.
+    fn detect(opts: &SignerOpts) -> Self {
+        let env = |name| std::env::var_os(name).is_some();
+
+        if opts.keystore.is_some() || opts.from.is_some() {
+            SignerKind::Keystore
+        } else if opts.mnemonic.is_some()
+            || opts.account_index.is_some()
+            || opts.derivation_path.is_some()
+        {
+            SignerKind::Mnemonic
+        } else if opts.signer_url.is_some() {
+            SignerKind::Remote
+        } else if env("ETH_PK") {
+            SignerKind::Key
+        } else if env("ETH_MNEMONIC") {
+            SignerKind::Mnemonic
+        } else if env("ETH_SIGNER_URL") {
+            SignerKind::Remote
+        } else {
+            SignerKind::Node
+        }
+    }
+}
+
+impl FromStr for SignerKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "key" => Ok(SignerKind::Key),
+            "keystore" => Ok(SignerKind::Keystore),
+            "mnemonic" => Ok(SignerKind::Mnemonic),
+            "node" => Ok(SignerKind::Node),
+            "remote" => Ok(SignerKind::Remote),
+            _ => Err(format!(
+                "unknown signer {}, expected one of {}",
+                s,
+                Self::VARIANTS.join(", ")
+            )),
+        }
+    }
+}
+
+impl Display for SignerKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerKind::Key => f.write_str("key"),
+            SignerKind::Keystore => f.write_str("keystore"),
+            SignerKind::Mnemonic => f.write_str("mnemonic"),
+            SignerKind::Node => f.write_str("node"),
+            SignerKind::Remote => f.write_str("remote"),
+        }
+    }
+}
+
+/// Something that signs transactions sent by the CLI.
+pub trait Signer: Debug {
+    /// Account to give to ethcontract when sending transactions.
+    fn account(&self) -> Account;
+
+    /// Remote signer that transactions should be routed through,
+    /// see [`SigningTransport`].
+    fn remote(&self) -> Option<&RemoteSigner> {
+        None
+    }
+}
+
+/// Choose the signer from the command line and environment.
+///
+/// Done before connecting to the node, so that conflicting options
+/// are reported right away.
+pub fn choose(opts: &SignerOpts) -> Result<SignerKind> {
+    let kind = match (opts.signer, std::env::var("ETH_SIGNER")) {
+        (Some(kind), _) => kind,
+        (None, Ok(kind)) => kind
+            .parse()
+            .map_err(|err| Error::Config(format!("invalid ETH_SIGNER: {}", err)))?,
+        (None, Err(_)) => SignerKind::detect(opts),
+    };
+    opts.check(kind)?;
+
+    Ok(kind)
+}
+
+/// Set up the signer of the given kind, see [`choose`].
+///
+/// Private keys sign transactions locally for the given chain; node and
+/// remote signers are expected to know the chain id on their own.
+pub async fn from_config(
+    opts: &SignerOpts,
+    kind: SignerKind,
+    chain_id: u64,
+) -> Result<Box<dyn Signer>> {
+    let signer: Box<dyn Signer> = match kind {
+        SignerKind::Key => {
+            let pk = std::env::var("ETH_PK").map_err(|_| {
+                Error::Config("environment variable ETH_PK must be present".to_string())
+            })?;
+            Box::new(PrivateKeySigner::from_hex(&pk, chain_id)?)
+        }
+
+        SignerKind::Keystore => {
+            let path = match (&opts.keystore, &opts.from) {
+                (Some(path), _) => path.clone(),
+                (None, Some(name)) => {
+                    let dir = crate::account::keystore_dir(opts.keystore_dir.as_deref())?;
+                    crate::account::stored_keystore_path(&dir, name)?
+                }
+                (None, None) => {
+                    return Err(Error::Config(
+                        "keystore signer needs --keystore or --from".to_string(),
+                    ))
+                }
+            };
+            Box::new(KeystoreSigner::unlock(&path, chain_id)?)
+        }
+
+        SignerKind::Mnemonic => {
+            let phrase = match &opts.mnemonic {
+                Some(phrase) => phrase.clone(),
+                None => std::env::var("ETH_MNEMONIC").map_err(|_| {
+                    Error::Config("mnemonic signer needs --mnemonic or ETH_MNEMONIC".to_string())
+                })?,
+            };
+            let path = opts.derivation_path.clone().unwrap_or_else(|| {
+                crate::mnemonic::default_derivation_path(opts.account_index.unwrap_or(0))
+            });
+            Box::new(MnemonicSigner::derive(&phrase, &path, chain_id)?)
+        }
+
+        SignerKind::Node => {
+            let address = std::env::var("ETH_ACCOUNT").map_err(|_| {
+                Error::Config(
+                    "environment variable ETH_PK, ETH_MNEMONIC or ETH_ACCOUNT must be present"
+                        .to_string(),
+                )
+            })?;
+            let password = std::env::var("ETH_PASSWORD").ok();
+            Box::new(NodeSigner::new(&address, password)?)
+        }
+
+        SignerKind::Remote => {
+            let url = opts
+                .signer_url
+                .clone()
+                .or_else(|| std::env::var("ETH_SIGNER_URL").ok())
+                .unwrap_or_else(|| "http://localhost:8550".to_string());
+            let address = match std::env::var("ETH_ACCOUNT") {
+                Ok(address) => Some(parse_account(&address)?),
+                Err(_) => None,
+            };
+            Box::new(RemoteSigner::connect(&url, address, chain_id).await?)
+        }
+    };
+
+    Ok(signer)
+}
+
+fn parse_account(address: &str) -> Result<Address> {
+    address.trim_start_matches("0x").parse().map_err(|_| {
+        Error::Config("environment variable ETH_ACCOUNT must contain valid address".to_string())
+    })
+}
+
+/// Private key given as is, in `ETH_PK`.
+#[derive(Debug)]
+pub struct PrivateKeySigner {
+    key: PrivateKey,
+    chain_id: u64,
+}
+
+impl PrivateKeySigner {
+    pub fn from_hex(key: &str, chain_id: u64) -> Result<Self> {
+        let key = PrivateKey::from_hex_str(key)
+            .map_err(|err| Error::Config(format!("invalid private key in ETH_PK: {}", err)))?;
+        Ok(PrivateKeySigner { key, chain_id })
+    }
+}
+
+impl Signer for PrivateKeySigner {
+    fn account(&self) -> Account {
+        Account::Offline(self.key.clone(), Some(self.chain_id))
+    }
+}
+
+/// Private key from an encrypted JSON keystore.
+#[derive(Debug)]
+pub struct KeystoreSigner {
+    key: PrivateKey,
+    chain_id: u64,
+}
+
+impl KeystoreSigner {
+    /// Ask for the keystore password on the terminal and decrypt the keystore.
+    pub fn unlock(path: &Path, chain_id: u64) -> Result<Self> {
+        let key = crate::keystore::unlock(path)?;
+        Ok(KeystoreSigner { key, chain_id })
+    }
+}
+
+impl Signer for KeystoreSigner {
+    fn account(&self) -> Account {
+        Account::Offline(self.key.clone(), Some(self.chain_id))
+    }
+}
+
+/// Private key derived from a BIP-39 mnemonic.
+#[derive(Debug)]
+pub struct MnemonicSigner {
+    key: PrivateKey,
+    chain_id: u64,
+}
+
+impl MnemonicSigner {
+    pub fn derive(phrase: &str, derivation_path: &str, chain_id: u64) -> Result<Self> {
+        let key = crate::mnemonic::derive_key(phrase, derivation_path)?;
+        Ok(MnemonicSigner { key, chain_id })
+    }
+}
+
+impl Signer for MnemonicSigner {
+    fn account(&self) -> Account {
+        Account::Offline(self.key.clone(), Some(self.chain_id))
+    }
+}
+
+/// Account managed by the node.
+///
+/// With a password, the node is asked to unlock the account for every
+/// transaction; without one, the account must already be unlocked.
+#[derive(Debug)]
+pub struct NodeSigner {
+    address: Address,
+    password: Option<Password>,
+}
+
+impl NodeSigner {
+    pub fn new(address: &str, password: Option<String>) -> Result<Self> {
+        Ok(NodeSigner {
+            address: parse_account(address)?,
+            password: password.map(Password::new),
+        })
+    }
+}
+
+impl Signer for NodeSigner {
+    fn account(&self) -> Account {
+        match &self.password {
+            Some(password) => Account::Locked(self.address, password.clone(), None),
+            None => Account::Local(self.address, None),
+        }
+    }
+}
+
+/// Separate process that signs transactions with `eth_signTransaction`,
+/// such as Clef.
+///
+/// ethcontract doesn't know about remote signers, so we give it a node
+/// account, and [`SigningTransport`] hands transactions over to the signer.
+#[derive(Clone, Debug)]
+pub struct RemoteSigner {
+    transport: Http,
+    address: Address,
+    chain_id: u64,
+}
+
+impl RemoteSigner {
+    /// Connect to the signer at `url`.
+    ///
+    /// If no address is given, the first account the signer knows is used.
+    pub async fn connect(url: &str, address: Option<Address>, chain_id: u64) -> Result<Self> {
+        let transport = Http::new(url)
+            .map_err(|err| Error::Config(format!("invalid signer url {}: {}", url, err)))?;
+
+        let address = match address {
+            Some(address) => address,
+            None => {
+                let accounts = Web3::new(transport.clone()).eth().accounts().await?;
+                *accounts
+                    .first()
+                    .ok_or_else(|| Error::Config(format!("signer at {} has no accounts", url)))?
+            }
+        };
+
+        Ok(RemoteSigner {
+            transport,
+            address,
+            chain_id,
+        })
+    }
+
+    /// Fill in what the signer needs to know about the transaction,
+    /// have it signed and send it to the node.
+    async fn send_transaction(&self, node: &Http, params: Vec<Value>) -> web3::Result<Value> {
+        let mut tx = match params.into_iter().next() {
+            Some(Value::Object(tx)) => tx,
+            _ => {
+                return Err(web3::Error::Decoder(
+                    "expected transaction object".to_string(),
+                ))
+            }
+        };
+
+        let missing = |tx: &ethcontract::json::Map<String, Value>, field| {
+            tx.get(field).is_none_or(Value::is_null)
+        };
+
+        tx.insert("from".to_string(), json!(self.address));
+        if missing(&tx, "nonce") {
+            let nonce = node
+                .execute(
+                    "eth_getTransactionCount",
+                    vec![json!(self.address), json!("pending")],
+                )
+                .await?;
+            tx.insert("nonce".to_string(), nonce);
+        }
+        if missing(&tx, "gasPrice") {
+            let gas_price = node.execute("eth_gasPrice", vec![]).await?;
+            tx.insert("gasPrice".to_string(), gas_price);
+        }
+        if missing(&tx, "chainId") {
+            tx.insert(
+                "chainId".to_string(),
+                json!(format!("{:#x}", self.chain_id)),
+            );
+        }
+
+        // Clef and geth return `{raw, tx}`, some signers return just the raw bytes.
+        let raw = match self
+            .transport
+            .execute("eth_signTransaction", vec![Value::Object(tx)])
+            .await?
+        {
+            Value::Object(mut signed) => signed.remove("raw"),
+            raw @ Value::String(_) => Some(raw),
+            _ => None,
+        }
+        .ok_or_else(|| {
+            web3::Error::InvalidResponse("signer didn't return raw transaction".to_string())
+        })?;
+
+        node.execute("eth_sendRawTransaction", vec![raw]).await
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn account(&self) -> Account {
+        Account::Local(self.address, None)
+    }
+
+    fn remote(&self) -> Option<&RemoteSigner> {
+        Some(self)
+    }
+}
+
+/// Connection to the node that routes `eth_sendTransaction` through
+/// a remote signer, if there is one.
+///
+/// Everything else, including batches, goes to the node as is.
+#[derive(Clone, Debug)]
+pub struct SigningTransport {
+    node: Http,
+    remote: Option<RemoteSigner>,
+}
+
+impl SigningTransport {
+    pub fn new(node: Http, remote: Option<RemoteSigner>) -> Self {
+        SigningTransport { node, remote }
+    }
+}
+
+impl Transport for SigningTransport {
+    type Out = BoxFuture<'static, web3::Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.node.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        if let (Some(remote), Call::MethodCall(call)) = (&self.remote, &request) {
+            if call.method == "eth_sendTransaction" {
+                let remote = remote.clone();
+                let node = self.node.clone();
+                let params = match &call.params {
+                    Params::Array(params) => params.clone(),
+                    _ => Vec::new(),
+                };
+                return async move { remote.send_transaction(&node, params).await }.boxed();
+            }
+        }
+
+        self.node.send(id, request).boxed()
+    }
+}
+
+impl BatchTransport for SigningTransport {
+    type Batch = BoxFuture<'static, web3::Result<Vec<web3::Result<Value>>>>;
+
+    fn send_batch<T>(&self, requests: T) -> Self::Batch
+    where
+        T: IntoIterator<Item = (RequestId, Call)>,
+    {
+        self.node.send_batch(requests).boxed()
+    }
+}
+
+#[cfg(test)]
+mod test_signer {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncBufReadExt as _, AsyncReadExt as _, AsyncWriteExt as _, BufReader};
+    use tokio::net::TcpListener;
+
+    const ACCOUNT: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+    const RAW_TX: &str = "0xf86c0a85";
+    const TX_HASH: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+
+    type Requests = Arc<Mutex<Vec<(String, Value)>>>;
+
+    /// Stand-in for both the node and the signer, answering JSON RPC
+    /// over HTTP with canned responses.
+    async fn stand_in_server() -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Requests::default();
+
+        let log = requests.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                tokio::spawn(serve_connection(stream, log.clone()));
+            }
+        });
+
+        (url, requests)
+    }
+
+    async fn serve_connection(stream: tokio::net::TcpStream, requests: Requests) {
+        let mut stream = BufReader::new(stream);
+        loop {
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                if stream.read_line(&mut line).await.unwrap() == 0 {
+                    return;
+                }
+                let line = line.trim_end().to_ascii_lowercase();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some(length) = line.strip_prefix("content-length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+
+            let mut body = vec![0; content_length];
+            stream.read_exact(&mut body).await.unwrap();
+            let request: Value = ethcontract::json::from_slice(&body).unwrap();
+
+            let method = request["method"].as_str().unwrap().to_string();
+            let params = request["params"].clone();
+            let result = match method.as_str() {
+                "eth_accounts" => json!([ACCOUNT]),
+                "eth_getTransactionCount" => json!("0xa"),
+                "eth_gasPrice" => json!("0x3b9aca00"),
+                "eth_signTransaction" => json!({ "raw": RAW_TX, "tx": params[0] }),
+                "eth_sendRawTransaction" => json!(TX_HASH),
+                "eth_blockNumber" => json!("0x2a"),
+                _ => json!(null),
+            };
+            requests.lock().unwrap().push((method, params));
+
+            let response = json!({ "jsonrpc": "2.0", "id": request["id"], "result": result });
+            let response = response.to_string();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\ncontent-type: application/json\r\ncontent-length: {}\r\n\r\n{}",
+                response.len(),
+                response
+            );
+            stream
+                .get_mut()
+                .write_all(response.as_bytes())
+                .await
+                .unwrap();
+        }
+    }
+
+    fn methods(requests: &Requests) -> Vec<String> {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(method, _)| method.clone())
+            .collect()
+    }
+
+    #[test]
+    fn signer_kind_from_str() {
+        for kind in SignerKind::VARIANTS {
+            assert_eq!(kind.parse::<SignerKind>().unwrap().to_string(), *kind);
+        }
+        assert!("clef".parse::<SignerKind>().is_err());
+    }
+
+    fn opts(args: &[&str]) -> SignerOpts {
+        use structopt::StructOpt;
+        SignerOpts::from_iter(std::iter::once("fake-ico").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn options_pick_signer() {
+        let index = opts(&["--account-index", "1"]);
+        assert_eq!(SignerKind::detect(&index), SignerKind::Mnemonic);
+        assert_eq!(index.check(SignerKind::Mnemonic), Ok(()));
+
+        let from = opts(&["--from", "alice", "--keystore-dir", "/tmp"]);
+        assert_eq!(SignerKind::detect(&from), SignerKind::Keystore);
+        assert_eq!(from.check(SignerKind::Keystore), Ok(()));
+    }
+
+    #[test]
+    fn options_for_other_signers_conflict() {
+        assert_eq!(
+            opts(&["--account-index", "1"]).check(SignerKind::Key),
+            Err(Error::Config(
+                "--account-index can't be used with the key signer".to_string()
+            ))
+        );
+        assert_eq!(
+            opts(&[
+                "--keystore",
+                "a.json",
+                "--signer-url",
+                "http://localhost:8550"
+            ])
+            .check(SignerKind::Keystore),
+            Err(Error::Config(
+                "--signer-url can't be used with the keystore signer".to_string()
+            ))
+        );
+        assert_eq!(
+            opts(&["--from", "alice", "--derivation-path", "m/0"]).check(SignerKind::Node),
+            Err(Error::Config(
+                "--from, --derivation-path can't be used with the node signer".to_string()
+            ))
+        );
+        assert_eq!(opts(&[]).check(SignerKind::Key), Ok(()));
+    }
+
+    #[test]
+    fn local_signers_use_chain_id() {
+        let signer = MnemonicSigner::derive(
+            "test test test test test test test test test test test junk",
+            &crate::mnemonic::default_derivation_path(0),
+            31337,
+        )
+        .unwrap();
+        assert_eq!(signer.account().address(), ACCOUNT.parse().unwrap());
+        assert!(matches!(signer.account(), Account::Offline(_, Some(31337))));
+    }
+
+    #[test]
+    fn node_signer_account() {
+        let signer = NodeSigner::new(ACCOUNT, None).unwrap();
+        assert!(matches!(signer.account(), Account::Local(_, None)));
+
+        let signer = NodeSigner::new(ACCOUNT, Some("password".to_string())).unwrap();
+        assert!(matches!(signer.account(), Account::Locked(_, _, None)));
+
+        assert!(matches!(
+            NodeSigner::new("0x1234", None),
+            Err(Error::Config(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn remote_signer_uses_first_account() {
+        let (url, _) = stand_in_server().await;
+        let signer = RemoteSigner::connect(&url, None, 31337).await.unwrap();
+        assert_eq!(signer.account().address(), ACCOUNT.parse().unwrap());
+        assert!(matches!(signer.account(), Account::Local(_, None)));
+    }
+
+    #[tokio::test]
+    async fn remote_signer_signs_transactions() {
+        let (url, requests) = stand_in_server().await;
+        let remote = RemoteSigner::connect(&url, Some(ACCOUNT.parse().unwrap()), 31337)
+            .await
+            .unwrap();
+        let transport = SigningTransport::new(Http::new(&url).unwrap(), Some(remote));
+
+        let hash = transport
+            .execute(
+                "eth_sendTransaction",
+                vec![json!({ "from": ACCOUNT, "to": ACCOUNT, "gas": "0x5208" })],
+            )
+            .await
+            .unwrap();
+        assert_eq!(hash, json!(TX_HASH));
+
+        assert_eq!(
+            methods(&requests),
+            [
+                "eth_getTransactionCount",
+                "eth_gasPrice",
+                "eth_signTransaction",
+                "eth_sendRawTransaction"
+            ]
+        );
+
+        let requests = requests.lock().unwrap();
+        assert_eq!(
+            requests[2].1,
+            json!([{
+                "from": ACCOUNT,
+                "to": ACCOUNT,
+                "gas": "0x5208",
+                "nonce": "0xa",
+                "gasPrice": "0x3b9aca00",
+                "chainId": "0x7a69",
+            }])
+        );
+        assert_eq!(requests[3].1, json!([RAW_TX]));
+    }
+
+    #[tokio::test]
+    async fn other_calls_go_to_node() {
+        let (url, requests) = stand_in_server().await;
+        let remote = RemoteSigner::connect(&url, Some(ACCOUNT.parse().unwrap()), 31337)
+            .await
+            .unwrap();
+        let web3 = Web3::new(SigningTransport::new(
+            Http::new(&url).unwrap(),
+            Some(remote),
+        ));
+
+        assert_eq!(web3.eth().block_number().await.unwrap(), 42.into());
+        assert_eq!(methods(&requests), ["eth_blockNumber"]);
+    }
+}
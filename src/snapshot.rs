@@ -0,0 +1,151 @@
+use ethcontract::batch::CallBatch;
+use ethcontract::prelude::*;
+use ethcontract::web3::types::U64;
+use std::fmt::{Display, Formatter};
+
+use crate::error::{Error, Result};
+use crate::signer::SigningTransport;
+
+/// State of the ICO, as reported by `ICO.state()`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum IcoState {
+    /// ICO accepts contributions.
+    Ongoing,
+
+    /// All SCM are sold, ICO is holding them until finish time.
+    Closed,
+
+    /// Purchased SCM can be claimed.
+    Finished,
+}
+
+impl IcoState {
+    /// Convert value returned by the contract to a typed state.
+    pub fn from_raw(state: u8) -> Result<Self> {
+        match state {
+            0 => Ok(IcoState::Ongoing),
+            1 => Ok(IcoState::Closed),
+            2 => Ok(IcoState::Finished),
+            unknown => Err(Error::Transport(format!("unknown ICO state {}", unknown))),
+        }
+    }
+}
+
+impl Display for IcoState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IcoState::Ongoing => f.write_str("Ongoing"),
+            IcoState::Closed => f.write_str("Closed"),
+            IcoState::Finished => f.write_str("Finished"),
+        }
+    }
+}
+
+/// Everything the ICO contract can tell about itself, as of a single block.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct IcoSnapshot {
+    /// Number of the block this snapshot was taken at.
+    pub block_number: U64,
+
+    /// Timestamp of the block this snapshot was taken at.
+    pub timestamp: u64,
+
+    pub state: IcoState,
+    pub left_eth: U256,
+    pub left_scm: U256,
+    pub target: U256,
+    pub rate: U256,
+    pub hold_duration: U256,
+
+    /// Time when ICO was closed, unknown while it's ongoing.
+    pub close_time: Option<u64>,
+
+    /// Time when ICO finishes, unknown while it's ongoing.
+    pub finish_time: Option<u64>,
+
+    pub scm: Address,
+    pub weth: Address,
+}
+
+impl IcoSnapshot {
+    /// Block this snapshot was taken at, for making more calls at that block.
+    pub fn block(&self) -> BlockId {
+        BlockId::Number(BlockNumber::Number(self.block_number))
+    }
+
+    /// Take a snapshot of the ICO at the latest block.
+    pub async fn fetch(
+        web3: &Web3<SigningTransport>,
+        contract: &crate::contracts::ICO,
+    ) -> Result<Self> {
+        let block = web3
+            .eth()
+            .block(BlockId::Number(BlockNumber::Latest))
+            .await?
+            .ok_or_else(|| Error::Transport("latest block is not available".to_string()))?;
+        let number = block
+            .number
+            .ok_or_else(|| Error::Transport("latest block has no number".to_string()))?;
+
+        let block_id = BlockId::Number(BlockNumber::Number(number));
+        let mut batch = CallBatch::new(web3.transport());
+
+        let state = contract.state().block(block_id).batch_call(&mut batch);
+        let left_eth = contract.left_eth().block(block_id).batch_call(&mut batch);
+        let left_scm = contract.left_scm().block(block_id).batch_call(&mut batch);
+        let target = contract.target().block(block_id).batch_call(&mut batch);
+        let rate = contract.rate().block(block_id).batch_call(&mut batch);
+        let hold_duration = contract
+            .hold_duration()
+            .block(block_id)
+            .batch_call(&mut batch);
+        let close_time = contract.close_time().block(block_id).batch_call(&mut batch);
+        let finish_time = contract
+            .finish_time()
+            .block(block_id)
+            .batch_call(&mut batch);
+        let scm = contract.scm().block(block_id).batch_call(&mut batch);
+        let weth = contract.weth().block(block_id).batch_call(&mut batch);
+
+        batch.execute_all(100).await;
+
+        let state = IcoState::from_raw(state.await?)?;
+
+        // Close and finish times revert while the ICO is ongoing.
+        let (close_time, finish_time) = match state {
+            IcoState::Ongoing => (None, None),
+            _ => (
+                Some(close_time.await?.as_u64()),
+                Some(finish_time.await?.as_u64()),
+            ),
+        };
+
+        Ok(IcoSnapshot {
+            block_number: number,
+            timestamp: block.timestamp.as_u64(),
+            state,
+            left_eth: left_eth.await?,
+            left_scm: left_scm.await?,
+            target: target.await?,
+            rate: rate.await?,
+            hold_duration: hold_duration.await?,
+            close_time,
+            finish_time,
+            scm: scm.await?,
+            weth: weth.await?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_ico_state {
+    use super::*;
+
+    #[test]
+    fn ico_state_from_raw() {
+        assert_eq!(IcoState::from_raw(0), Ok(IcoState::Ongoing));
+        assert_eq!(IcoState::from_raw(1), Ok(IcoState::Closed));
+        assert_eq!(IcoState::from_raw(2), Ok(IcoState::Finished));
+        assert!(matches!(IcoState::from_raw(3), Err(Error::Transport(_))));
+    }
+}